@@ -0,0 +1,297 @@
+mod simulator;
+
+use std::fmt;
+
+use crate::card::SensitiveAuthData;
+use crate::money::Money;
+use crate::transaction::{Transaction, TransactionId};
+
+pub use simulator::SimulatedAcquirer;
+
+/// Why the issuer or the acquirer turned a request down, normalised across acquirers.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
+#[non_exhaustive]
+pub enum DeclineReason {
+    InsufficientFunds,
+    ExpiredCard,
+    SuspectedFraud,
+    InvalidCvv,
+    DoNotHonor,
+    IssuerUnavailable,
+}
+
+/// Whether retrying a declined request may succeed.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
+#[non_exhaustive]
+pub enum DeclineKind {
+    /// The same card may be approved later, e.g. once there are funds on it.
+    Soft,
+    /// Retrying with the same card data will be declined again and may be flagged by the
+    /// card network.
+    Hard,
+}
+
+impl DeclineReason {
+    /// Maps an ISO 8583 response code, as most acquirers send it, to a decline reason, or
+    /// to `None` for the codes that approve the request. Declines without a more specific
+    /// meaning are a generic do not honor.
+    pub fn from_response_code(code: &str) -> Option<DeclineReason> {
+        let reason = match code {
+            "00" | "08" | "10" | "11" => return None,
+            "51" | "61" => DeclineReason::InsufficientFunds,
+            "33" | "54" => DeclineReason::ExpiredCard,
+            "34" | "59" | "41" | "43" => DeclineReason::SuspectedFraud,
+            "N7" | "82" => DeclineReason::InvalidCvv,
+            "91" | "96" => DeclineReason::IssuerUnavailable,
+            _ => DeclineReason::DoNotHonor,
+        };
+        Some(reason)
+    }
+
+    pub fn kind(&self) -> DeclineKind {
+        match self {
+            DeclineReason::InsufficientFunds | DeclineReason::DoNotHonor | DeclineReason::IssuerUnavailable => DeclineKind::Soft,
+            DeclineReason::ExpiredCard | DeclineReason::SuspectedFraud | DeclineReason::InvalidCvv => DeclineKind::Hard,
+        }
+    }
+}
+
+impl fmt::Display for DeclineReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self {
+            DeclineReason::InsufficientFunds => "insufficient funds",
+            DeclineReason::ExpiredCard => "expired card",
+            DeclineReason::SuspectedFraud => "suspected fraud",
+            DeclineReason::InvalidCvv => "invalid CVV",
+            DeclineReason::DoNotHonor => "do not honor",
+            DeclineReason::IssuerUnavailable => "issuer unavailable",
+        };
+        f.write_str(reason)
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
+#[non_exhaustive]
+pub enum AuthorizationOutcome {
+    Approved { approval_code: String },
+    Declined(DeclineReason),
+}
+
+/// An authorization result whose outcome contradicts its response code, e.g. an approval
+/// sent with `51`.
+#[derive(PartialEq, Debug)]
+#[non_exhaustive]
+pub enum AuthorizationError {
+    ResponseCodeMismatch { response_code: String, approved: bool },
+}
+
+impl fmt::Display for AuthorizationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuthorizationError::ResponseCodeMismatch { response_code, approved: true } => {
+                write!(f, "response code {response_code:?} declines, it cannot come with an approval")
+            }
+            AuthorizationError::ResponseCodeMismatch { response_code, approved: false } => {
+                write!(f, "response code {response_code:?} approves, it cannot come with a decline")
+            }
+        }
+    }
+}
+
+impl std::error::Error for AuthorizationError {}
+
+/// What the acquirer answered to a request it managed to process: an authorization, or the
+/// capture, void or refund of one.
+#[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "AuthorizationRecord", try_from = "AuthorizationRecord")
+)]
+pub struct AuthorizationResult {
+    outcome: AuthorizationOutcome,
+    nsu: String,
+    response_code: String,
+}
+
+impl AuthorizationResult {
+    /// Reads the acquirer's answer off its raw `response_code`, approving with
+    /// `approval_code` only when `DeclineReason::from_response_code` finds no decline in it.
+    /// The approval code is ignored on declines, which acquirers usually send blank. `nsu` is
+    /// the unique sequence number (Número Sequencial Único) the acquirer gave the request.
+    pub fn from_response_code(response_code: impl Into<String>, nsu: impl Into<String>, approval_code: impl Into<String>) -> Self {
+        let response_code = response_code.into();
+        let outcome = match DeclineReason::from_response_code(&response_code) {
+            None => AuthorizationOutcome::Approved { approval_code: approval_code.into() },
+            Some(reason) => AuthorizationOutcome::Declined(reason),
+        };
+        AuthorizationResult { outcome, nsu: nsu.into(), response_code }
+    }
+
+    /// An approval, as long as `response_code` is one of the codes that approve.
+    pub fn approved(approval_code: impl Into<String>, nsu: impl Into<String>, response_code: impl Into<String>) -> Result<Self, AuthorizationError> {
+        let outcome = AuthorizationOutcome::Approved { approval_code: approval_code.into() };
+        AuthorizationResult { outcome, nsu: nsu.into(), response_code: response_code.into() }.checked()
+    }
+
+    /// A decline for `reason`, as long as `response_code` is one of the codes that decline.
+    /// The reason is kept as given, since acquirers map their own codes to it.
+    pub fn declined(reason: DeclineReason, nsu: impl Into<String>, response_code: impl Into<String>) -> Result<Self, AuthorizationError> {
+        let outcome = AuthorizationOutcome::Declined(reason);
+        AuthorizationResult { outcome, nsu: nsu.into(), response_code: response_code.into() }.checked()
+    }
+
+    fn checked(self) -> Result<Self, AuthorizationError> {
+        let approved = self.is_approved();
+        if DeclineReason::from_response_code(&self.response_code).is_none() != approved {
+            return Err(AuthorizationError::ResponseCodeMismatch { response_code: self.response_code, approved });
+        }
+        Ok(self)
+    }
+
+    pub fn outcome(&self) -> &AuthorizationOutcome {
+        &self.outcome
+    }
+
+    pub fn is_approved(&self) -> bool {
+        matches!(self.outcome, AuthorizationOutcome::Approved { .. })
+    }
+
+    pub fn approval_code(&self) -> Option<&str> {
+        match &self.outcome {
+            AuthorizationOutcome::Approved { approval_code } => Some(approval_code),
+            AuthorizationOutcome::Declined(_) => None,
+        }
+    }
+
+    pub fn decline_reason(&self) -> Option<DeclineReason> {
+        match self.outcome {
+            AuthorizationOutcome::Approved { .. } => None,
+            AuthorizationOutcome::Declined(reason) => Some(reason),
+        }
+    }
+
+    pub fn decline_kind(&self) -> Option<DeclineKind> {
+        self.decline_reason().map(|reason| reason.kind())
+    }
+
+    pub fn nsu(&self) -> &str {
+        &self.nsu
+    }
+
+    /// The response code exactly as the acquirer sent it.
+    pub fn response_code(&self) -> &str {
+        &self.response_code
+    }
+}
+
+/// What an authorization result is serialized as, so a deserialized one is checked like
+/// `approved` and `declined` check it.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct AuthorizationRecord {
+    outcome: AuthorizationOutcome,
+    nsu: String,
+    response_code: String,
+}
+
+#[cfg(feature = "serde")]
+impl From<AuthorizationResult> for AuthorizationRecord {
+    fn from(result: AuthorizationResult) -> Self {
+        AuthorizationRecord { outcome: result.outcome, nsu: result.nsu, response_code: result.response_code }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<AuthorizationRecord> for AuthorizationResult {
+    type Error = AuthorizationError;
+
+    fn try_from(record: AuthorizationRecord) -> Result<Self, Self::Error> {
+        AuthorizationResult { outcome: record.outcome, nsu: record.nsu, response_code: record.response_code }.checked()
+    }
+}
+
+/// The acquirer could not process a request at all, as opposed to declining it.
+#[derive(PartialEq, Debug)]
+#[non_exhaustive]
+pub enum AcquirerError {
+    Unavailable(String),
+    UnknownTransaction(TransactionId),
+}
+
+impl fmt::Display for AcquirerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AcquirerError::Unavailable(details) => write!(f, "acquirer unavailable: {details}"),
+            AcquirerError::UnknownTransaction(id) => write!(f, "acquirer does not know transaction {id}"),
+        }
+    }
+}
+
+impl std::error::Error for AcquirerError {}
+
+/// A connection to the network that gets card payments approved by the issuer.
+pub trait Acquirer {
+    /// Asks the issuer to reserve the value of `tx` on the card.
+    fn authorize(&mut self, tx: &Transaction, auth: &SensitiveAuthData) -> Result<AuthorizationResult, AcquirerError>;
+
+    /// Captures `amount` out of an authorization.
+    fn capture(&mut self, tx: &Transaction, amount: Money) -> Result<AuthorizationResult, AcquirerError>;
+
+    /// Releases an authorization that will not be captured.
+    fn void(&mut self, tx: &Transaction) -> Result<AuthorizationResult, AcquirerError>;
+
+    /// Gives `amount` of a captured transaction back to the cardholder.
+    fn refund(&mut self, tx: &Transaction, amount: Money) -> Result<AuthorizationResult, AcquirerError>;
+}
+
+impl<A: Acquirer + ?Sized> Acquirer for &mut A {
+    fn authorize(&mut self, tx: &Transaction, auth: &SensitiveAuthData) -> Result<AuthorizationResult, AcquirerError> {
+        (**self).authorize(tx, auth)
+    }
+
+    fn capture(&mut self, tx: &Transaction, amount: Money) -> Result<AuthorizationResult, AcquirerError> {
+        (**self).capture(tx, amount)
+    }
+
+    fn void(&mut self, tx: &Transaction) -> Result<AuthorizationResult, AcquirerError> {
+        (**self).void(tx)
+    }
+
+    fn refund(&mut self, tx: &Transaction, amount: Money) -> Result<AuthorizationResult, AcquirerError> {
+        (**self).refund(tx, amount)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_never_read_an_approval_code_as_a_decline() {
+        assert_eq!(DeclineReason::from_response_code("00"), None);
+        assert_eq!(DeclineReason::from_response_code("51"), Some(DeclineReason::InsufficientFunds));
+        assert_eq!(DeclineReason::from_response_code("N7"), Some(DeclineReason::InvalidCvv));
+        assert_eq!(DeclineReason::from_response_code("Z9"), Some(DeclineReason::DoNotHonor));
+    }
+
+    #[test]
+    fn should_refuse_results_that_contradict_their_response_code() {
+        assert_eq!(
+            AuthorizationResult::approved("000001", "000000001", "51"),
+            Err(AuthorizationError::ResponseCodeMismatch { response_code: "51".to_owned(), approved: true })
+        );
+        assert_eq!(
+            AuthorizationResult::declined(DeclineReason::DoNotHonor, "000000001", "00"),
+            Err(AuthorizationError::ResponseCodeMismatch { response_code: "00".to_owned(), approved: false })
+        );
+
+        let declined = AuthorizationResult::from_response_code("54", "000000002", "");
+        assert_eq!((declined.decline_reason(), declined.approval_code()), (Some(DeclineReason::ExpiredCard), None));
+        let approved = AuthorizationResult::from_response_code("00", "000000003", "000001");
+        assert_eq!(Ok(approved), AuthorizationResult::approved("000001", "000000003", "00"));
+    }
+}
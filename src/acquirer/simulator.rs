@@ -0,0 +1,203 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use crate::acquirer::{Acquirer, AcquirerError, AuthorizationResult};
+use crate::card::SensitiveAuthData;
+use crate::money::Money;
+use crate::transaction::{Transaction, TransactionId};
+
+/// Test cards as `(BIN, last four digits, response code)`. Any other valid card is approved.
+const DECLINED_CARDS: &[(&str, &str, &str)] = &[
+    ("40000000", "0002", "05"),
+    ("40000000", "9995", "51"),
+    ("40000000", "0069", "54"),
+    ("41000000", "0019", "59"),
+    ("40000000", "0127", "N7"),
+];
+
+/// Amounts whose minor units end in these two digits are declined whatever the card, so a
+/// decline can be triggered with an approved card too, e.g. BRL 10.51 or JPY 1051.
+const DECLINED_CENTS: &[(i64, &str)] = &[(51, "51"), (5, "05"), (91, "91")];
+
+const APPROVED: &str = "00";
+
+#[derive(Debug)]
+struct SimulatedAuthorization {
+    authorized: Money,
+    captured: Money,
+    refunded: Money,
+    voided: bool,
+}
+
+/// An acquirer that never leaves the process, for integration tests. It answers the same
+/// way every time for the same input:
+///
+/// | card                | decline reason       |
+/// |---------------------|----------------------|
+/// | 4000 0000 0000 0002 | do not honor         |
+/// | 4000 0000 0000 9995 | insufficient funds   |
+/// | 4000 0000 0000 0069 | expired card         |
+/// | 4100 0000 0000 0019 | suspected fraud      |
+/// | 4000 0000 0000 0127 | invalid CVV          |
+///
+/// Amounts whose minor units end in `51` are declined for insufficient funds, in `05` with
+/// do not honor and in `91` as if the issuer were unavailable, whatever the currency's
+/// exponent: BRL 10.51 and JPY 1051 are both declined. Amounts that are not positive are
+/// declined too, and so is a second authorization of the same transaction. Everything else
+/// is approved with a sequential approval code. Every answer, approved or not, gets the
+/// next NSU and the ISO 8583 response code a real acquirer would send.
+#[derive(Debug, Default)]
+pub struct SimulatedAcquirer {
+    authorizations: HashMap<TransactionId, SimulatedAuthorization>,
+    next_code: u32,
+    next_nsu: u64,
+}
+
+impl SimulatedAcquirer {
+    pub fn new() -> Self {
+        SimulatedAcquirer::default()
+    }
+
+    fn nsu(&mut self) -> String {
+        self.next_nsu += 1;
+        format!("{:09}", self.next_nsu)
+    }
+
+    fn approve(&mut self) -> AuthorizationResult {
+        self.next_code += 1;
+        let approval_code = format!("{:06}", self.next_code);
+        AuthorizationResult::from_response_code(APPROVED, self.nsu(), approval_code)
+    }
+
+    fn decline(&mut self, response_code: &str) -> AuthorizationResult {
+        AuthorizationResult::from_response_code(response_code, self.nsu(), "")
+    }
+
+    fn authorization(&mut self, tx: &Transaction) -> Result<&mut SimulatedAuthorization, AcquirerError> {
+        self.authorizations.get_mut(&tx.id()).ok_or(AcquirerError::UnknownTransaction(tx.id()))
+    }
+}
+
+/// Whether `amount` is more than `limit`, which an amount in another currency always is.
+fn exceeds(amount: Money, limit: Money) -> bool {
+    !matches!(amount.checked_cmp(&limit), Ok(Ordering::Less | Ordering::Equal))
+}
+
+fn is_positive(amount: Money) -> bool {
+    amount.amount() > 0
+}
+
+impl Acquirer for SimulatedAcquirer {
+    fn authorize(&mut self, tx: &Transaction, _auth: &SensitiveAuthData) -> Result<AuthorizationResult, AcquirerError> {
+        if self.authorizations.contains_key(&tx.id()) {
+            return Ok(self.decline("05"));
+        }
+        let card = tx.card();
+        let by_card = DECLINED_CARDS
+            .iter()
+            .find(|(bin, last_four, _)| card.bin() == *bin && card.number() == *last_four)
+            .map(|(_, _, code)| *code);
+        let cents = tx.value().amount().rem_euclid(100);
+        let by_amount = DECLINED_CENTS.iter().find(|(declined, _)| cents == *declined).map(|(_, code)| *code);
+        if let Some(code) = by_card.or(by_amount) {
+            return Ok(self.decline(code));
+        }
+        if !is_positive(tx.value()) {
+            return Ok(self.decline("05"));
+        }
+
+        let zero = Money::zero(tx.currency());
+        self.authorizations
+            .insert(tx.id(), SimulatedAuthorization { authorized: tx.value(), captured: zero, refunded: zero, voided: false });
+        Ok(self.approve())
+    }
+
+    fn capture(&mut self, tx: &Transaction, amount: Money) -> Result<AuthorizationResult, AcquirerError> {
+        let authorization = self.authorization(tx)?;
+        if authorization.voided || !authorization.captured.is_zero() || !is_positive(amount) || exceeds(amount, authorization.authorized) {
+            return Ok(self.decline("05"));
+        }
+        authorization.captured = amount;
+        Ok(self.approve())
+    }
+
+    fn void(&mut self, tx: &Transaction) -> Result<AuthorizationResult, AcquirerError> {
+        let authorization = self.authorization(tx)?;
+        if authorization.voided || !authorization.captured.is_zero() {
+            return Ok(self.decline("05"));
+        }
+        authorization.voided = true;
+        Ok(self.approve())
+    }
+
+    fn refund(&mut self, tx: &Transaction, amount: Money) -> Result<AuthorizationResult, AcquirerError> {
+        let authorization = self.authorization(tx)?;
+        match authorization.refunded.checked_add(amount) {
+            Ok(refunded) if is_positive(amount) && !exceeds(refunded, authorization.captured) => authorization.refunded = refunded,
+            _ => return Ok(self.decline("05")),
+        }
+        Ok(self.approve())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acquirer::DeclineReason;
+    use crate::test_support::{brl, card_numbered, merchant};
+    use crate::transaction::PaymentMethod;
+
+    fn authorize(acquirer: &mut SimulatedAcquirer, number: &str, value: Money) -> (Transaction, AuthorizationResult) {
+        let card = card_numbered(number);
+        let auth = SensitiveAuthData::try_new("123".to_owned(), &card).unwrap();
+        let tx = Transaction::authorize(&merchant(), value, "Test Transaction".to_owned(), PaymentMethod::Credit, card).unwrap();
+        let response = acquirer.authorize(&tx, &auth).unwrap();
+        (tx, response)
+    }
+
+    #[test]
+    fn should_decline_the_well_known_test_cards_and_amounts() {
+        let mut acquirer = SimulatedAcquirer::new();
+        let declines: Vec<_> = ["4000000000000002", "4000000000009995", "4000000000000069", "4100000000000019", "4000000000000127"]
+            .iter()
+            .map(|number| authorize(&mut acquirer, number, brl(10000)).1)
+            .map(|result| (result.decline_reason(), result.response_code().to_owned()))
+            .collect();
+
+        assert_eq!(
+            declines,
+            [
+                (Some(DeclineReason::DoNotHonor), "05".to_owned()),
+                (Some(DeclineReason::InsufficientFunds), "51".to_owned()),
+                (Some(DeclineReason::ExpiredCard), "54".to_owned()),
+                (Some(DeclineReason::SuspectedFraud), "59".to_owned()),
+                (Some(DeclineReason::InvalidCvv), "N7".to_owned()),
+            ]
+        );
+        assert_eq!(authorize(&mut acquirer, "4111111111111111", brl(1051)).1.decline_reason(), Some(DeclineReason::InsufficientFunds));
+        assert_eq!(authorize(&mut acquirer, "4111111111111111", brl(1005)).1.decline_reason(), Some(DeclineReason::DoNotHonor));
+        assert_eq!(authorize(&mut acquirer, "4111111111111111", brl(1091)).1.decline_reason(), Some(DeclineReason::IssuerUnavailable));
+        assert_eq!(authorize(&mut acquirer, "4111111111111111", brl(0)).1.decline_reason(), Some(DeclineReason::DoNotHonor));
+    }
+
+    #[test]
+    fn should_track_what_was_authorized_captured_and_refunded() {
+        let mut acquirer = SimulatedAcquirer::new();
+        let (tx, result) = authorize(&mut acquirer, "4111111111111111", brl(10000));
+
+        assert_eq!(Ok(result), AuthorizationResult::approved("000001", "000000001", "00"));
+        assert_eq!(acquirer.capture(&tx, brl(10001)), Ok(AuthorizationResult::declined(DeclineReason::DoNotHonor, "000000002", "05").unwrap()));
+        assert!(!acquirer.capture(&tx, brl(0)).unwrap().is_approved());
+        assert!(acquirer.capture(&tx, brl(8000)).unwrap().is_approved());
+        assert!(!acquirer.refund(&tx, brl(-1000)).unwrap().is_approved());
+        assert!(acquirer.refund(&tx, brl(8000)).unwrap().is_approved());
+        assert!(!acquirer.refund(&tx, brl(1)).unwrap().is_approved());
+        assert!(!acquirer.void(&tx).unwrap().is_approved());
+        let auth = SensitiveAuthData::try_new("123".to_owned(), tx.card()).unwrap();
+        assert!(!acquirer.authorize(&tx, &auth).unwrap().is_approved());
+        assert!(!acquirer.capture(&tx, brl(2000)).unwrap().is_approved());
+
+        let (unknown, _) = authorize(&mut SimulatedAcquirer::new(), "4111111111111111", brl(10000));
+        assert_eq!(acquirer.void(&unknown), Err(AcquirerError::UnknownTransaction(unknown.id())));
+    }
+}
@@ -0,0 +1,209 @@
+use std::fmt;
+
+use chrono::NaiveDate;
+
+use crate::clock::Clock;
+use crate::merchant::Merchant;
+use crate::money::{Money, MoneyError, Rate, RoundingMode};
+use crate::payable::{Payable, PayableStatus};
+
+const DAYS_PER_MONTH: u32 = 30;
+
+#[derive(PartialEq, Debug)]
+#[non_exhaustive]
+pub enum AnticipationError {
+    NothingToAnticipate,
+    NotWaitingFunds { index: usize },
+    OnHold { index: usize },
+    MerchantMismatch { index: usize, expected: String, found: String },
+    AlreadyDue { index: usize, due_date: NaiveDate },
+    Money(MoneyError),
+}
+
+impl fmt::Display for AnticipationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AnticipationError::NothingToAnticipate => f.write_str("no payables to anticipate"),
+            AnticipationError::NotWaitingFunds { index } => write!(f, "payable {index} is not waiting for funds"),
+            AnticipationError::OnHold { index } => write!(f, "payable {index} is on hold"),
+            AnticipationError::MerchantMismatch { index, expected, found } => {
+                write!(f, "payable {index} belongs to merchant {found:?}, not {expected:?}")
+            }
+            AnticipationError::AlreadyDue { index, due_date } => write!(f, "payable {index} is already due on {due_date}"),
+            AnticipationError::Money(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for AnticipationError {}
+
+impl From<MoneyError> for AnticipationError {
+    fn from(err: MoneyError) -> Self {
+        AnticipationError::Money(err)
+    }
+}
+
+/// How much one payable is worth when received early.
+#[derive(PartialEq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AnticipatedItem {
+    pub due_date: NaiveDate,
+    pub days_early: u32,
+    pub gross: Money,
+    pub discount: Money,
+    pub net: Money,
+}
+
+/// What a merchant would receive by anticipating a set of payables on `date`.
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AnticipationQuote {
+    pub date: NaiveDate,
+    pub items: Vec<AnticipatedItem>,
+    pub gross: Money,
+    pub discount: Money,
+    pub net: Money,
+}
+
+/// The outcome of committing an anticipation: the quote it was priced at and the new paid
+/// payables, one per anticipated payable.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Anticipation {
+    pub quote: AnticipationQuote,
+    pub payables: Vec<Payable>,
+}
+
+/// Prices early settlement of `WaitingFunds` payables with simple interest at a monthly
+/// rate, pro rata over the days left until each one is due.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct Anticipator {
+    monthly_rate: Rate,
+    rounding: RoundingMode,
+}
+
+impl Anticipator {
+    pub fn new(monthly_rate: Rate) -> Self {
+        Anticipator { monthly_rate, rounding: RoundingMode::default() }
+    }
+
+    pub fn with_rounding(mut self, rounding: RoundingMode) -> Self {
+        self.rounding = rounding;
+        self
+    }
+
+    /// Simulates anticipating `payables` on `date` without touching them.
+    pub fn quote(&self, payables: &[Payable], date: NaiveDate) -> Result<AnticipationQuote, AnticipationError> {
+        let first = payables.first().ok_or(AnticipationError::NothingToAnticipate)?;
+        let zero = Money::zero(first.currency());
+        let mut quote = AnticipationQuote { date, items: Vec::with_capacity(payables.len()), gross: zero, discount: zero, net: zero };
+
+        for (index, payable) in payables.iter().enumerate() {
+            if *payable.status() != PayableStatus::WaitingFunds {
+                return Err(AnticipationError::NotWaitingFunds { index });
+            }
+            if payable.is_on_hold() {
+                return Err(AnticipationError::OnHold { index });
+            }
+            let days_early = (payable.due_date() - date).num_days();
+            if days_early <= 0 {
+                return Err(AnticipationError::AlreadyDue { index, due_date: payable.due_date() });
+            }
+            let days_early = u32::try_from(days_early).map_err(|_| MoneyError::Overflow)?;
+            let gross = payable.net();
+            let discount = gross.apply_rate_pro_rata(self.monthly_rate, days_early, DAYS_PER_MONTH, self.rounding)?;
+            let net = gross.checked_sub(discount)?;
+
+            quote.gross = quote.gross.checked_add(gross)?;
+            quote.discount = quote.discount.checked_add(discount)?;
+            quote.net = quote.net.checked_add(net)?;
+            quote.items.push(AnticipatedItem { due_date: payable.due_date(), days_early, gross, discount, net });
+        }
+        Ok(quote)
+    }
+
+    /// Anticipates `payables` now: marks each of them as anticipated and pays the discounted
+    /// amount out in a new payable settled today for `merchant`, who has to be the one they
+    /// belong to. Nothing is changed if any of them cannot be anticipated.
+    pub fn anticipate(&self, payables: &mut [Payable], merchant: &Merchant, clock: &impl Clock) -> Result<Anticipation, AnticipationError> {
+        if let Some((index, payable)) = payables.iter().enumerate().find(|(_, payable)| payable.transaction().merchant_id() != merchant.id()) {
+            let (expected, found) = (merchant.id().to_owned(), payable.transaction().merchant_id().to_owned());
+            return Err(AnticipationError::MerchantMismatch { index, expected, found });
+        }
+        let now = clock.now();
+        let date = merchant.local_date(now);
+        let quote = self.quote(payables, date)?;
+        let paid = payables
+            .iter_mut()
+            .zip(&quote.items)
+            .map(|(payable, item)| {
+                payable
+                    .transition_to(PayableStatus::Anticipated, now)
+                    .expect("quote only accepts payables waiting for funds");
+                Payable::new(PayableStatus::Paid, payable.transaction().clone(), payable.installment(), item.net, Money::zero(item.net.currency()), date, Some(date))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Anticipation { quote, payables: paid })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{brl, clock_on, date, installments, merchant, merchant_named};
+
+    #[test]
+    fn should_quote_without_touching_the_payables() {
+        let payables = installments();
+        let anticipator = Anticipator::new(Rate::from_basis_points(200));
+
+        let quote = anticipator.quote(&payables, date(2024, 3, 16)).unwrap();
+
+        // 30 and 60 days early at 2% a month
+        assert_eq!(quote.items.iter().map(|item| item.days_early).collect::<Vec<_>>(), vec![30, 60]);
+        // what the merchant would have received once fees are taken
+        assert_eq!(quote.gross, brl(9500));
+        assert_eq!(quote.discount, brl(285));
+        assert_eq!(quote.net, brl(9215));
+        assert!(payables.iter().all(|payable| *payable.status() == PayableStatus::WaitingFunds));
+    }
+
+    #[test]
+    fn should_mark_the_originals_as_anticipated_and_pay_the_net_amount() {
+        let mut payables = installments();
+        let anticipator = Anticipator::new(Rate::from_basis_points(200));
+
+        let anticipation = anticipator.anticipate(&mut payables, &merchant(), &clock_on(2024, 3, 16)).unwrap();
+
+        assert!(payables.iter().all(|payable| *payable.status() == PayableStatus::Anticipated));
+        assert!(payables.iter().all(|payable| payable.history().len() == 1));
+        let paid: Vec<_> = anticipation.payables.iter().map(|payable| (payable.net(), payable.settlement_date())).collect();
+        assert_eq!(paid, vec![(brl(4655), Some(date(2024, 3, 16))), (brl(4560), Some(date(2024, 3, 16)))]);
+        assert!(anticipation.payables.iter().all(|payable| *payable.status() == PayableStatus::Paid));
+    }
+
+    #[test]
+    fn should_refuse_payables_that_cannot_be_anticipated() {
+        let mut payables = installments();
+        let anticipator = Anticipator::new(Rate::from_basis_points(200));
+
+        assert_eq!(anticipator.quote(&[], date(2024, 3, 16)).unwrap_err(), AnticipationError::NothingToAnticipate);
+        assert_eq!(
+            anticipator.quote(&payables, date(2024, 4, 15)).unwrap_err(),
+            AnticipationError::AlreadyDue { index: 0, due_date: date(2024, 4, 15) }
+        );
+
+        let other = merchant_named("merchant-2");
+        assert_eq!(
+            anticipator.anticipate(&mut payables, &other, &clock_on(2024, 3, 16)).unwrap_err(),
+            AnticipationError::MerchantMismatch { index: 0, expected: "merchant-2".to_owned(), found: "merchant-1".to_owned() }
+        );
+
+        anticipator.anticipate(&mut payables[..1], &merchant(), &clock_on(2024, 3, 16)).unwrap();
+        assert_eq!(
+            anticipator.anticipate(&mut payables, &merchant(), &clock_on(2024, 3, 16)).unwrap_err(),
+            AnticipationError::NotWaitingFunds { index: 0 }
+        );
+        assert_eq!(*payables[1].status(), PayableStatus::WaitingFunds);
+    }
+}
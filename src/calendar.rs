@@ -0,0 +1,249 @@
+use std::collections::BTreeSet;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use chrono::{Datelike, Days, NaiveDate, Weekday};
+
+pub const DEFAULT_DAYS_FOR_CREDIT_PAYABLE: u64 = 30;
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum CalendarError {
+    Io(io::Error),
+    InvalidDate { line: usize, value: String },
+    NoBusinessDays,
+}
+
+impl fmt::Display for CalendarError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CalendarError::Io(err) => write!(f, "could not read holidays: {err}"),
+            CalendarError::InvalidDate { line, value } => write!(f, "invalid holiday {value:?} on line {line}, expected YYYY-MM-DD"),
+            CalendarError::NoBusinessDays => f.write_str("a calendar needs at least one business day a week"),
+        }
+    }
+}
+
+impl std::error::Error for CalendarError {}
+
+impl From<io::Error> for CalendarError {
+    fn from(err: io::Error) -> Self {
+        CalendarError::Io(err)
+    }
+}
+
+const WEEK: [Weekday; 7] = [Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri, Weekday::Sat, Weekday::Sun];
+
+/// The days money moves: everything but the weekend and the listed holidays.
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "CalendarRecord", try_from = "CalendarRecord")
+)]
+pub struct BusinessCalendar {
+    weekend: Vec<Weekday>,
+    holidays: BTreeSet<NaiveDate>,
+}
+
+impl Default for BusinessCalendar {
+    fn default() -> Self {
+        BusinessCalendar { weekend: vec![Weekday::Sat, Weekday::Sun], holidays: BTreeSet::new() }
+    }
+}
+
+impl BusinessCalendar {
+    /// A calendar whose days off are `weekend`. It has to leave at least one business day a
+    /// week, or there would be no day to roll forward to.
+    pub fn new(weekend: Vec<Weekday>) -> Result<Self, CalendarError> {
+        if WEEK.iter().all(|day| weekend.contains(day)) {
+            return Err(CalendarError::NoBusinessDays);
+        }
+        Ok(BusinessCalendar { weekend, holidays: BTreeSet::new() })
+    }
+
+    pub fn with_holiday(mut self, date: NaiveDate) -> Self {
+        self.holidays.insert(date);
+        self
+    }
+
+    /// Adds the holidays listed in `contents`, one `YYYY-MM-DD` per line. Anything after the
+    /// date is taken as the holiday name and ignored, as are blank lines and `#` comments.
+    pub fn with_holidays_from_str(mut self, contents: &str) -> Result<Self, CalendarError> {
+        for (index, line) in contents.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let Some(value) = line.split_whitespace().next() else {
+                continue;
+            };
+            let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .map_err(|_| CalendarError::InvalidDate { line: index + 1, value: value.to_owned() })?;
+            self.holidays.insert(date);
+        }
+        Ok(self)
+    }
+
+    pub fn with_holidays_from_file(self, path: impl AsRef<Path>) -> Result<Self, CalendarError> {
+        let contents = fs::read_to_string(path)?;
+        self.with_holidays_from_str(&contents)
+    }
+
+    pub fn is_business_day(&self, date: NaiveDate) -> bool {
+        !self.weekend.contains(&date.weekday()) && !self.holidays.contains(&date)
+    }
+
+    /// The first business day on or after `date`.
+    pub fn roll_forward(&self, date: NaiveDate) -> NaiveDate {
+        let mut date = date;
+        while !self.is_business_day(date) {
+            date = date + Days::new(1);
+        }
+        date
+    }
+
+    /// The business day `days` business days after `date`.
+    pub fn add_business_days(&self, date: NaiveDate, days: u64) -> NaiveDate {
+        let mut date = date;
+        for _ in 0..days {
+            date = self.roll_forward(date + Days::new(1));
+        }
+        date
+    }
+}
+
+/// What a calendar is serialized as, so a deserialized one is checked like `new` checks it.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct CalendarRecord {
+    weekend: Vec<Weekday>,
+    holidays: BTreeSet<NaiveDate>,
+}
+
+#[cfg(feature = "serde")]
+impl From<BusinessCalendar> for CalendarRecord {
+    fn from(calendar: BusinessCalendar) -> Self {
+        CalendarRecord { weekend: calendar.weekend, holidays: calendar.holidays }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<CalendarRecord> for BusinessCalendar {
+    type Error = CalendarError;
+
+    fn try_from(record: CalendarRecord) -> Result<Self, Self::Error> {
+        let calendar = BusinessCalendar::new(record.weekend)?;
+        Ok(BusinessCalendar { holidays: record.holidays, ..calendar })
+    }
+}
+
+/// How far from the transaction date a credit payable is due.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
+#[non_exhaustive]
+pub enum SettlementRule {
+    /// Counts calendar days, then moves to the next business day if it lands on a day off.
+    CalendarDaysThenRollForward(u64),
+    /// Counts business days only.
+    BusinessDays(u64),
+}
+
+/// A settlement rule plus the calendar it is counted on.
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SettlementSchedule {
+    rule: SettlementRule,
+    calendar: BusinessCalendar,
+}
+
+impl Default for SettlementSchedule {
+    fn default() -> Self {
+        SettlementSchedule::new(
+            SettlementRule::CalendarDaysThenRollForward(DEFAULT_DAYS_FOR_CREDIT_PAYABLE),
+            BusinessCalendar::default(),
+        )
+    }
+}
+
+impl SettlementSchedule {
+    pub fn new(rule: SettlementRule, calendar: BusinessCalendar) -> Self {
+        SettlementSchedule { rule, calendar }
+    }
+
+    pub fn calendar(&self) -> &BusinessCalendar {
+        &self.calendar
+    }
+
+    pub fn due_date(&self, from: NaiveDate) -> NaiveDate {
+        match self.rule {
+            SettlementRule::CalendarDaysThenRollForward(days) => self.calendar.roll_forward(from + Days::new(days)),
+            SettlementRule::BusinessDays(days) => self.calendar.add_business_days(from, days),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    const BRAZILIAN_HOLIDAYS: &str = "\
+# Feriados nacionais 2024
+2024-01-01 Confraternização Universal
+2024-03-29 Sexta-feira Santa
+2024-04-21 Tiradentes
+
+2024-05-01 Dia do Trabalho
+";
+
+    #[test]
+    fn should_load_holidays_skipping_comments_and_blank_lines() {
+        let calendar = BusinessCalendar::default().with_holidays_from_str(BRAZILIAN_HOLIDAYS).unwrap();
+
+        assert!(!calendar.is_business_day(date(2024, 3, 29)));
+        assert!(!calendar.is_business_day(date(2024, 5, 1)));
+        assert!(calendar.is_business_day(date(2024, 4, 30)));
+    }
+
+    #[test]
+    fn should_point_at_the_line_of_an_invalid_holiday() {
+        let result = BusinessCalendar::default().with_holidays_from_str("2024-01-01\n2024-13-01 Nope\n");
+
+        assert!(matches!(result, Err(CalendarError::InvalidDate { line: 2, value }) if value == "2024-13-01"));
+    }
+
+    #[test]
+    fn should_refuse_a_calendar_without_business_days() {
+        assert!(matches!(BusinessCalendar::new(WEEK.to_vec()), Err(CalendarError::NoBusinessDays)));
+        assert!(BusinessCalendar::new(vec![Weekday::Sun]).unwrap().is_business_day(date(2024, 3, 16)));
+    }
+
+    #[test]
+    fn should_roll_forward_over_weekends_and_holidays() {
+        let calendar = BusinessCalendar::default().with_holidays_from_str(BRAZILIAN_HOLIDAYS).unwrap();
+
+        // Friday 2024-03-29 is Good Friday, then the weekend
+        assert_eq!(calendar.roll_forward(date(2024, 3, 29)), date(2024, 4, 1));
+        assert_eq!(calendar.roll_forward(date(2024, 4, 2)), date(2024, 4, 2));
+    }
+
+    #[test]
+    fn should_count_calendar_days_then_roll_forward() {
+        let schedule = SettlementSchedule::default();
+
+        // 2024-03-15 + 30 days is Sunday 2024-04-14
+        assert_eq!(schedule.due_date(date(2024, 3, 15)), date(2024, 4, 15));
+    }
+
+    #[test]
+    fn should_count_business_days_only() {
+        let calendar = BusinessCalendar::default().with_holidays_from_str(BRAZILIAN_HOLIDAYS).unwrap();
+        let schedule = SettlementSchedule::new(SettlementRule::BusinessDays(2), calendar);
+
+        // Thursday, skipping Good Friday and the weekend
+        assert_eq!(schedule.due_date(date(2024, 3, 28)), date(2024, 4, 2));
+    }
+}
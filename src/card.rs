@@ -0,0 +1,334 @@
+mod brand;
+mod expiry;
+
+use std::fmt;
+
+use zeroize::Zeroize;
+
+use crate::Unmasked;
+
+pub use brand::CardBrand;
+pub use expiry::Expiry;
+
+const CARD_DIGITS_TO_SAVE: usize = 4;
+const SHORT_BIN_LENGTH: usize = 6;
+const LONG_BIN_LENGTH: usize = 8;
+const MIN_CARD_NUMBER_LENGTH: usize = 12;
+const MAX_CARD_NUMBER_LENGTH: usize = 19;
+
+/// Only the BIN and the last four digits of the number are kept, so they are all that can
+/// ever be serialized. The holder is serialized as initials and the expiry not at all, the
+/// same way `Debug` masks them. The CVV lives in [`SensitiveAuthData`], which cannot be
+/// serialized.
+#[derive(PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(into = "CardRecord", try_from = "CardRecord"))]
+pub struct Card {
+    number: String,
+    bin: String,
+    brand: CardBrand,
+    holder: String,
+    expires_at: Option<Expiry>,
+}
+
+#[derive(PartialEq, Debug)]
+#[non_exhaustive]
+pub enum CardError {
+    TooShort { length: usize },
+    TooLong { length: usize },
+    NonDigit,
+    LuhnFailure,
+    UnknownBrand,
+    InvalidLengthForBrand { brand: CardBrand, length: usize },
+    EmptyHolder,
+    MalformedExpiry(String),
+    InvalidCvvLength { expected: usize, length: usize },
+    InvalidTruncation { bin: usize, last_four: usize },
+}
+
+impl fmt::Display for CardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CardError::TooShort { length } => write!(f, "card number has {length} digits, expected at least {MIN_CARD_NUMBER_LENGTH}"),
+            CardError::TooLong { length } => write!(f, "card number has {length} digits, expected at most {MAX_CARD_NUMBER_LENGTH}"),
+            CardError::NonDigit => f.write_str("card number must contain only digits"),
+            CardError::LuhnFailure => f.write_str("card number failed the Luhn check"),
+            CardError::UnknownBrand => f.write_str("card number does not belong to a supported brand"),
+            CardError::InvalidLengthForBrand { brand, length } => write!(f, "{brand} card numbers cannot have {length} digits"),
+            CardError::EmptyHolder => f.write_str("card holder must not be empty"),
+            CardError::MalformedExpiry(expires_at) => write!(f, "malformed card expiry {expires_at:?}, expected MM/YY, MM/YYYY or YYMM"),
+            CardError::InvalidCvvLength { expected, length } => write!(f, "CVV has {length} digits, expected {expected}"),
+            CardError::InvalidTruncation { bin, last_four } => write!(
+                f,
+                "stored cards keep a {SHORT_BIN_LENGTH} or {LONG_BIN_LENGTH} digit BIN and the last {CARD_DIGITS_TO_SAVE} digits, found {bin} and {last_four}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CardError {}
+
+/// Whether `digits` pass the Luhn (mod 10) checksum. Expects ASCII digits only.
+fn passes_luhn(digits: &str) -> bool {
+    let sum: u32 = digits
+        .bytes()
+        .rev()
+        .map(|b| (b - b'0') as u32)
+        .enumerate()
+        .map(|(i, digit)| match (i % 2 == 1, digit * 2) {
+            (true, doubled) if doubled > 9 => doubled - 9,
+            (true, doubled) => doubled,
+            (false, _) => digit,
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+impl Card {
+    /// Validates the card data, detects the brand and keeps only the BIN and the last four
+    /// digits of the number.
+    pub fn try_new(number: String, holder: String, expires_at: String) -> Result<Self, CardError> {
+        if !number.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(CardError::NonDigit);
+        }
+        if number.len() < MIN_CARD_NUMBER_LENGTH {
+            return Err(CardError::TooShort { length: number.len() });
+        }
+        if number.len() > MAX_CARD_NUMBER_LENGTH {
+            return Err(CardError::TooLong { length: number.len() });
+        }
+        if !passes_luhn(&number) {
+            return Err(CardError::LuhnFailure);
+        }
+        let brand = CardBrand::detect(&number).ok_or(CardError::UnknownBrand)?;
+        if !brand.accepts_length(number.len()) {
+            return Err(CardError::InvalidLengthForBrand { brand, length: number.len() });
+        }
+        if holder.trim().is_empty() {
+            return Err(CardError::EmptyHolder);
+        }
+        let expires_at = Expiry::parse(&expires_at)?;
+
+        let bin_length = if number.len() >= 16 { LONG_BIN_LENGTH } else { SHORT_BIN_LENGTH };
+        let last_four_digits = number[number.len() - CARD_DIGITS_TO_SAVE..].to_string();
+        Ok(Card {
+            number: last_four_digits,
+            bin: number[..bin_length].to_string(),
+            brand,
+            holder,
+            expires_at: Some(expires_at),
+        })
+    }
+
+    pub fn number(&self) -> &str {
+        &self.number
+    }
+
+    pub fn bin(&self) -> &str {
+        &self.bin
+    }
+
+    pub fn brand(&self) -> CardBrand {
+        self.brand
+    }
+
+    pub fn holder(&self) -> &str {
+        &self.holder
+    }
+
+    /// When the card expires. Unknown for a card restored from storage, since the expiry is
+    /// never serialized.
+    pub fn expires_at(&self) -> Option<Expiry> {
+        self.expires_at
+    }
+
+    pub fn unmasked(&self) -> Unmasked<'_, Card> {
+        Unmasked(self)
+    }
+
+    fn masked_number(&self) -> String {
+        format!("**** **** **** {}", self.number)
+    }
+
+    fn holder_initials(&self) -> String {
+        self.holder
+            .split_whitespace()
+            .filter_map(|name| name.chars().next())
+            .map(|initial| format!("{initial}."))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+impl fmt::Debug for Card {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Card")
+            .field("number", &self.masked_number())
+            .field("brand", &self.brand)
+            .field("holder", &self.holder_initials())
+            .field("expires_at", &"**/**")
+            .finish()
+    }
+}
+
+impl fmt::Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} ({})", self.brand, self.masked_number(), self.holder_initials())
+    }
+}
+
+impl fmt::Debug for Unmasked<'_, Card> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Card")
+            .field("number", &self.0.masked_number())
+            .field("bin", &self.0.bin)
+            .field("brand", &self.0.brand)
+            .field("holder", &self.0.holder)
+            .field("expires_at", &self.0.expires_at.map(|expiry| expiry.to_string()))
+            .finish()
+    }
+}
+
+/// How a card is serialized. Deserializing goes through it too, so a payload carrying more
+/// than the last four digits or a BIN of the wrong length is rejected instead of stored.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CardRecord {
+    last_four: String,
+    bin: String,
+    brand: CardBrand,
+    holder: String,
+}
+
+#[cfg(feature = "serde")]
+impl From<Card> for CardRecord {
+    fn from(card: Card) -> Self {
+        let holder = card.holder_initials();
+        CardRecord { last_four: card.number, bin: card.bin, brand: card.brand, holder }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<CardRecord> for Card {
+    type Error = CardError;
+
+    fn try_from(record: CardRecord) -> Result<Self, Self::Error> {
+        let all_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+        if !all_digits(&record.last_four) || !all_digits(&record.bin) {
+            return Err(CardError::NonDigit);
+        }
+        if record.last_four.len() != CARD_DIGITS_TO_SAVE || ![SHORT_BIN_LENGTH, LONG_BIN_LENGTH].contains(&record.bin.len()) {
+            return Err(CardError::InvalidTruncation { bin: record.bin.len(), last_four: record.last_four.len() });
+        }
+        if record.holder.trim().is_empty() {
+            return Err(CardError::EmptyHolder);
+        }
+        Ok(Card { number: record.last_four, bin: record.bin, brand: record.brand, holder: record.holder, expires_at: None })
+    }
+}
+
+/// Data that may only be used to authorize a transaction and must never be stored after
+/// that (PCI DSS requirement 3.2). It is kept apart from `Card` so it cannot outlive the
+/// authorization, and is wiped from memory when dropped.
+pub struct SensitiveAuthData {
+    cvv: String,
+}
+
+impl SensitiveAuthData {
+    pub fn try_new(mut cvv: String, card: &Card) -> Result<Self, CardError> {
+        let expected = card.brand.cvv_length();
+        if cvv.len() != expected || !cvv.bytes().all(|b| b.is_ascii_digit()) {
+            let length = cvv.len();
+            cvv.zeroize();
+            return Err(CardError::InvalidCvvLength { expected, length });
+        }
+        Ok(SensitiveAuthData { cvv })
+    }
+
+    pub fn cvv(&self) -> &str {
+        &self.cvv
+    }
+}
+
+impl fmt::Debug for SensitiveAuthData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SensitiveAuthData").field("cvv", &"<redacted>").finish()
+    }
+}
+
+impl Drop for SensitiveAuthData {
+    fn drop(&mut self) {
+        self.cvv.zeroize();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn card() -> Card {
+        Card::try_new("4111111111111111".to_owned(), "Rafael Dias".to_owned(), "12/30".to_owned()).unwrap()
+    }
+    #[test]
+    fn should_create_a_card_but_hiding_the_last_four_digits() {
+        let number = "4111111111111111".to_owned();
+        let holder = "Rafael Dias".to_owned();
+        let expires_at = "12/30".to_owned();
+        let card = Card::try_new(number, holder.clone(), expires_at).unwrap();
+        assert_eq!(card.number, "1111");
+        assert_eq!(card.holder, holder);
+        assert_eq!(card.expires_at, Some(Expiry::new(12, 2030).unwrap()));
+    }
+
+    #[test]
+    fn should_reject_invalid_card_data_instead_of_panicking() {
+        let try_new = |number: &str, holder: &str, expires_at: &str| {
+            Card::try_new(number.to_owned(), holder.to_owned(), expires_at.to_owned())
+        };
+
+        assert_eq!(try_new("123", "Rafael Dias", "12/30"), Err(CardError::TooShort { length: 3 }));
+        assert_eq!(try_new("41111111111111111111", "Rafael Dias", "12/30"), Err(CardError::TooLong { length: 20 }));
+        assert_eq!(try_new("4111 1111 1111 1111", "Rafael Dias", "12/30"), Err(CardError::NonDigit));
+        assert_eq!(try_new("4111111111111112", "Rafael Dias", "12/30"), Err(CardError::LuhnFailure));
+        assert_eq!(try_new("4111111111111111", "  ", "12/30"), Err(CardError::EmptyHolder));
+        assert_eq!(try_new("4111111111111111", "Rafael Dias", "13/30"), Err(CardError::MalformedExpiry("13/30".to_owned())));
+        assert_eq!(try_new("9999999999999995", "Rafael Dias", "12/30"), Err(CardError::UnknownBrand));
+        assert_eq!(
+            try_new("5555555555554", "Rafael Dias", "12/30"),
+            Err(CardError::InvalidLengthForBrand { brand: CardBrand::Mastercard, length: 13 })
+        );
+    }
+
+    #[test]
+    fn should_validate_the_cvv_against_the_card_brand_without_storing_it_on_the_card() {
+        let visa = card();
+        let amex = Card::try_new("378282246310005".to_owned(), "Rafael Dias".to_owned(), "12/30".to_owned()).unwrap();
+
+        assert_eq!(SensitiveAuthData::try_new("123".to_owned(), &visa).unwrap().cvv(), "123");
+        assert_eq!(SensitiveAuthData::try_new("1234".to_owned(), &amex).unwrap().cvv(), "1234");
+        assert_eq!(SensitiveAuthData::try_new("12".to_owned(), &visa).unwrap_err(), CardError::InvalidCvvLength { expected: 3, length: 2 });
+        assert_eq!(SensitiveAuthData::try_new("123".to_owned(), &amex).unwrap_err(), CardError::InvalidCvvLength { expected: 4, length: 3 });
+        assert_eq!(SensitiveAuthData::try_new("12a".to_owned(), &visa).unwrap_err(), CardError::InvalidCvvLength { expected: 3, length: 3 });
+    }
+
+    #[test]
+    fn should_redact_the_cvv_from_debug_output() {
+        let auth = SensitiveAuthData::try_new("789".to_owned(), &card()).unwrap();
+
+        let debug = format!("{auth:?}");
+
+        assert!(!debug.contains("789"));
+    }
+
+    #[test]
+    fn should_keep_the_brand_and_bin_of_the_card() {
+        let visa = card();
+        assert_eq!(visa.brand(), CardBrand::Visa);
+        assert_eq!(visa.bin(), "41111111");
+
+        let amex = Card::try_new("378282246310005".to_owned(), "Rafael Dias".to_owned(), "12/30".to_owned()).unwrap();
+        assert_eq!(amex.brand(), CardBrand::Amex);
+        assert_eq!(amex.bin(), "378282");
+        assert_eq!(amex.number(), "0005");
+    }
+}
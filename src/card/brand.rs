@@ -0,0 +1,149 @@
+use std::fmt;
+
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
+#[non_exhaustive]
+pub enum CardBrand {
+    Visa,
+    Mastercard,
+    Amex,
+    Elo,
+    Hipercard,
+    Discover,
+    Jcb,
+    Diners,
+}
+
+/// IIN ranges as `(first, last, brand)`, compared against as many leading digits of the
+/// card number as `first` has. Elo and Hipercard come first because their ranges sit
+/// inside the ones claimed by Visa, Mastercard and Discover.
+const IIN_RANGES: &[(u32, u32, CardBrand)] = &[
+    (401178, 401179, CardBrand::Elo),
+    (431274, 431274, CardBrand::Elo),
+    (438935, 438935, CardBrand::Elo),
+    (451416, 451416, CardBrand::Elo),
+    (457393, 457393, CardBrand::Elo),
+    (457631, 457632, CardBrand::Elo),
+    (504175, 504175, CardBrand::Elo),
+    (506699, 506778, CardBrand::Elo),
+    (509000, 509999, CardBrand::Elo),
+    (627780, 627780, CardBrand::Elo),
+    (636297, 636297, CardBrand::Elo),
+    (636368, 636368, CardBrand::Elo),
+    (650031, 650033, CardBrand::Elo),
+    (650035, 650051, CardBrand::Elo),
+    (650405, 650439, CardBrand::Elo),
+    (650485, 650538, CardBrand::Elo),
+    (650541, 650598, CardBrand::Elo),
+    (650700, 650718, CardBrand::Elo),
+    (650720, 650727, CardBrand::Elo),
+    (650901, 650978, CardBrand::Elo),
+    (651652, 651679, CardBrand::Elo),
+    (655000, 655019, CardBrand::Elo),
+    (655021, 655058, CardBrand::Elo),
+    (384100, 384100, CardBrand::Hipercard),
+    (384140, 384140, CardBrand::Hipercard),
+    (384160, 384160, CardBrand::Hipercard),
+    (606282, 606282, CardBrand::Hipercard),
+    (637095, 637095, CardBrand::Hipercard),
+    (637568, 637568, CardBrand::Hipercard),
+    (637599, 637599, CardBrand::Hipercard),
+    (637609, 637609, CardBrand::Hipercard),
+    (637612, 637612, CardBrand::Hipercard),
+    (34, 34, CardBrand::Amex),
+    (37, 37, CardBrand::Amex),
+    (3095, 3095, CardBrand::Diners),
+    (300, 305, CardBrand::Diners),
+    (36, 36, CardBrand::Diners),
+    (38, 39, CardBrand::Diners),
+    (3528, 3589, CardBrand::Jcb),
+    (6011, 6011, CardBrand::Discover),
+    (622126, 622925, CardBrand::Discover),
+    (644, 649, CardBrand::Discover),
+    (65, 65, CardBrand::Discover),
+    (2221, 2720, CardBrand::Mastercard),
+    (51, 55, CardBrand::Mastercard),
+    (4, 4, CardBrand::Visa),
+];
+
+fn digit_count(mut value: u32) -> usize {
+    let mut count = 1;
+    while value >= 10 {
+        value /= 10;
+        count += 1;
+    }
+    count
+}
+
+impl CardBrand {
+    /// Detects the brand from the IIN of `number`, which must contain only ASCII digits.
+    pub fn detect(number: &str) -> Option<CardBrand> {
+        IIN_RANGES.iter().find_map(|&(first, last, brand)| {
+            let prefix = number.get(..digit_count(first))?.parse::<u32>().ok()?;
+            (first..=last).contains(&prefix).then_some(brand)
+        })
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CardBrand::Visa => "Visa",
+            CardBrand::Mastercard => "Mastercard",
+            CardBrand::Amex => "American Express",
+            CardBrand::Elo => "Elo",
+            CardBrand::Hipercard => "Hipercard",
+            CardBrand::Discover => "Discover",
+            CardBrand::Jcb => "JCB",
+            CardBrand::Diners => "Diners Club",
+        }
+    }
+
+    pub fn accepts_length(&self, length: usize) -> bool {
+        match self {
+            CardBrand::Visa => matches!(length, 13 | 16 | 19),
+            CardBrand::Mastercard | CardBrand::Elo => length == 16,
+            CardBrand::Amex => length == 15,
+            CardBrand::Hipercard => matches!(length, 13 | 16 | 19),
+            CardBrand::Discover | CardBrand::Jcb => (16..=19).contains(&length),
+            CardBrand::Diners => (14..=19).contains(&length),
+        }
+    }
+
+    pub fn cvv_length(&self) -> usize {
+        match self {
+            CardBrand::Amex => 4,
+            _ => 3,
+        }
+    }
+}
+
+impl fmt::Display for CardBrand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_detect_the_brand_from_well_known_test_numbers() {
+        assert_eq!(CardBrand::detect("4111111111111111"), Some(CardBrand::Visa));
+        assert_eq!(CardBrand::detect("5555555555554444"), Some(CardBrand::Mastercard));
+        assert_eq!(CardBrand::detect("2223000048400011"), Some(CardBrand::Mastercard));
+        assert_eq!(CardBrand::detect("378282246310005"), Some(CardBrand::Amex));
+        assert_eq!(CardBrand::detect("6362970000457013"), Some(CardBrand::Elo));
+        assert_eq!(CardBrand::detect("6062825624254001"), Some(CardBrand::Hipercard));
+        assert_eq!(CardBrand::detect("6011111111111117"), Some(CardBrand::Discover));
+        assert_eq!(CardBrand::detect("3530111333300000"), Some(CardBrand::Jcb));
+        assert_eq!(CardBrand::detect("36227206271667"), Some(CardBrand::Diners));
+        assert_eq!(CardBrand::detect("9999999999999995"), None);
+    }
+
+    #[test]
+    fn should_prefer_elo_over_the_brands_sharing_its_prefixes() {
+        assert_eq!(CardBrand::detect("4011780000000000"), Some(CardBrand::Elo));
+        assert_eq!(CardBrand::detect("5090000000000000"), Some(CardBrand::Elo));
+        assert_eq!(CardBrand::detect("6504050000000000"), Some(CardBrand::Elo));
+    }
+}
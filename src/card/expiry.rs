@@ -0,0 +1,115 @@
+use std::fmt;
+
+use chrono::{Datelike, NaiveDate};
+
+use crate::card::CardError;
+
+/// The month a card expires in. A card is valid up to and including the last day of it.
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub struct Expiry {
+    month: u32,
+    year: i32,
+}
+
+impl Expiry {
+    const CENTURY: i32 = 2000;
+
+    pub fn new(month: u32, year: i32) -> Result<Self, CardError> {
+        if !(1..=12).contains(&month) {
+            return Err(CardError::MalformedExpiry(format!("{month:02}/{year}")));
+        }
+        Ok(Expiry { month, year })
+    }
+
+    /// Parses `MM/YY`, `MM/YYYY` or the `YYMM` used on the magnetic stripe.
+    pub fn parse(value: &str) -> Result<Self, CardError> {
+        let malformed = || CardError::MalformedExpiry(value.to_owned());
+        let all_digits = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+        let (month, year) = match value.split_once('/') {
+            Some((month, year)) if month.len() == 2 && matches!(year.len(), 2 | 4) => (month, year),
+            Some(_) => return Err(malformed()),
+            None if value.len() == 4 => match (value.get(2..), value.get(..2)) {
+                (Some(month), Some(year)) => (month, year),
+                _ => return Err(malformed()),
+            },
+            None => return Err(malformed()),
+        };
+        if !all_digits(month) || !all_digits(year) {
+            return Err(malformed());
+        }
+
+        let month: u32 = month.parse().map_err(|_| malformed())?;
+        let mut year: i32 = year.parse().map_err(|_| malformed())?;
+        if year < 100 {
+            year += Self::CENTURY;
+        }
+        Expiry::new(month, year).map_err(|_| malformed())
+    }
+
+    pub fn month(&self) -> u32 {
+        self.month
+    }
+
+    pub fn year(&self) -> i32 {
+        self.year
+    }
+
+    pub fn is_expired(&self, at: NaiveDate) -> bool {
+        (at.year(), at.month()) > (self.year, self.month)
+    }
+}
+
+impl fmt::Display for Expiry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}/{:02}", self.month, self.year % 100)
+    }
+}
+
+/// Serialized as `MM/YYYY` so the century survives the round trip.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Expiry {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&format_args!("{:02}/{}", self.month, self.year))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Expiry {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        Expiry::parse(&value).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_the_common_expiry_formats() {
+        assert_eq!(Expiry::parse("12/30"), Expiry::new(12, 2030));
+        assert_eq!(Expiry::parse("01/2031"), Expiry::new(1, 2031));
+        assert_eq!(Expiry::parse("3005"), Expiry::new(5, 2030));
+        assert_eq!(Expiry::parse("13/30"), Err(CardError::MalformedExpiry("13/30".to_owned())));
+        assert_eq!(Expiry::parse("1/30"), Err(CardError::MalformedExpiry("1/30".to_owned())));
+        assert_eq!(Expiry::parse("ab/cd"), Err(CardError::MalformedExpiry("ab/cd".to_owned())));
+        assert_eq!(Expiry::parse("123"), Err(CardError::MalformedExpiry("123".to_owned())));
+        assert_eq!(Expiry::parse("aé1"), Err(CardError::MalformedExpiry("aé1".to_owned())));
+    }
+
+    #[test]
+    fn should_be_valid_until_the_last_day_of_the_month() {
+        let expiry = Expiry::new(2, 2028).unwrap();
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+
+        assert!(!expiry.is_expired(date(2027, 12, 31)));
+        assert!(!expiry.is_expired(date(2028, 2, 29)));
+        assert!(expiry.is_expired(date(2028, 3, 1)));
+        assert!(expiry.is_expired(date(2029, 1, 1)));
+    }
+
+    #[test]
+    fn should_display_as_month_and_two_digit_year() {
+        assert_eq!(Expiry::new(5, 2030).unwrap().to_string(), "05/30");
+    }
+}
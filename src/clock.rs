@@ -0,0 +1,88 @@
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+
+/// Where the crate gets the current time from, so dates can be pinned in tests and when
+/// replaying historical transactions.
+pub trait Clock {
+    fn now(&self) -> DateTime<Utc>;
+
+    /// The current date in UTC. Use `Merchant::local_date` for the merchant's own calendar.
+    fn today(&self) -> NaiveDate {
+        self.now().date_naive()
+    }
+}
+
+/// Reads the time from the operating system.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// Always returns the same instant.
+#[derive(Debug, Clone, Copy)]
+pub struct FixedClock {
+    now: DateTime<Utc>,
+}
+
+impl FixedClock {
+    pub fn new(now: DateTime<Utc>) -> Self {
+        FixedClock { now }
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.now
+    }
+}
+
+/// Shifts another clock by a fixed amount, e.g. to replay yesterday's transactions.
+#[derive(Debug, Clone, Copy)]
+pub struct OffsetClock<C> {
+    inner: C,
+    offset: Duration,
+}
+
+impl<C: Clock> OffsetClock<C> {
+    pub fn new(inner: C, offset: Duration) -> Self {
+        OffsetClock { inner, offset }
+    }
+}
+
+impl<C: Clock> Clock for OffsetClock<C> {
+    fn now(&self) -> DateTime<Utc> {
+        self.inner.now() + self.offset
+    }
+}
+
+impl<C: Clock + ?Sized> Clock for &C {
+    fn now(&self) -> DateTime<Utc> {
+        (**self).now()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn should_always_return_the_fixed_instant() {
+        let instant = Utc.with_ymd_and_hms(2024, 3, 15, 12, 0, 0).unwrap();
+        let clock = FixedClock::new(instant);
+
+        assert_eq!(clock.now(), instant);
+        assert_eq!(clock.now(), instant);
+    }
+
+    #[test]
+    fn should_shift_the_inner_clock_by_the_offset() {
+        let instant = Utc.with_ymd_and_hms(2024, 3, 15, 12, 0, 0).unwrap();
+        let clock = OffsetClock::new(FixedClock::new(instant), Duration::days(-1));
+
+        assert_eq!(clock.now(), Utc.with_ymd_and_hms(2024, 3, 14, 12, 0, 0).unwrap());
+    }
+}
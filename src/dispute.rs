@@ -0,0 +1,537 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use chrono::{DateTime, NaiveDate, Utc};
+
+use crate::clock::Clock;
+use crate::fees::FeePolicy;
+use crate::merchant::Merchant;
+use crate::money::{Money, MoneyError};
+use crate::payable::{Installment, Payable, PayableStatus};
+use crate::transaction::TransactionId;
+
+/// Why the cardholder disputes a transaction, normalised across card networks.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
+#[non_exhaustive]
+pub enum DisputeReason {
+    Fraud,
+    NotReceived,
+    NotAsDescribed,
+    Duplicate,
+    CreditNotProcessed,
+    Other,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
+#[non_exhaustive]
+pub enum DisputeStatus {
+    Opened,
+    EvidenceSubmitted,
+    Won,
+    Lost,
+}
+
+impl DisputeStatus {
+    /// Whether a dispute may move from this status to `to`. Evidence can only be sent
+    /// while the dispute is open, and a won or lost dispute is final.
+    pub fn can_transition_to(&self, to: DisputeStatus) -> bool {
+        use DisputeStatus::*;
+        matches!((self, to), (Opened, EvidenceSubmitted | Won | Lost) | (EvidenceSubmitted, Won | Lost))
+    }
+}
+
+#[derive(PartialEq, Debug)]
+#[non_exhaustive]
+pub enum DisputeError {
+    NothingToDispute,
+    MixedTransactions,
+    InvalidAmount(Money),
+    ExceedsDisputable { amount: Money, disputable: Money },
+    MerchantMismatch { expected: String, found: String },
+    IllegalTransition { from: DisputeStatus, to: DisputeStatus },
+    DeadlinePassed { respond_by: NaiveDate },
+    InconsistentStatus { status: DisputeStatus },
+    Money(MoneyError),
+}
+
+impl fmt::Display for DisputeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DisputeError::NothingToDispute => f.write_str("no payables to dispute"),
+            DisputeError::MixedTransactions => f.write_str("payables belong to more than one transaction"),
+            DisputeError::InvalidAmount(amount) => write!(f, "cannot dispute {amount}, disputes must be positive"),
+            DisputeError::ExceedsDisputable { amount, disputable } => {
+                write!(f, "cannot dispute {amount}, only {disputable} was captured and not refunded or charged back")
+            }
+            DisputeError::MerchantMismatch { expected, found } => {
+                write!(f, "dispute belongs to merchant {found:?}, not {expected:?}")
+            }
+            DisputeError::IllegalTransition { from, to } => write!(f, "a {from:?} dispute cannot become {to:?}"),
+            DisputeError::DeadlinePassed { respond_by } => write!(f, "evidence was due by {respond_by}"),
+            DisputeError::InconsistentStatus { status } => {
+                write!(f, "a {status:?} dispute does not match its evidence and closing time")
+            }
+            DisputeError::Money(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for DisputeError {}
+
+impl From<MoneyError> for DisputeError {
+    fn from(err: MoneyError) -> Self {
+        DisputeError::Money(err)
+    }
+}
+
+/// A chargeback the cardholder raised against a transaction, from the moment the acquirer
+/// reports it until it is won or lost.
+///
+/// Opening a dispute puts the payables of the transaction still waiting for funds on hold,
+/// so the merchant is not paid out money that may have to be returned. Winning it releases
+/// the hold; losing it charges back the payables it covers and debits the merchant for
+/// what had already been paid out through a chargeback payable.
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "DisputeRecord", try_from = "DisputeRecord")
+)]
+pub struct Dispute {
+    transaction_id: TransactionId,
+    merchant_id: String,
+    amount: Money,
+    refunded_at_open: Money,
+    reason: DisputeReason,
+    reason_code: String,
+    opened_at: DateTime<Utc>,
+    respond_by: NaiveDate,
+    status: DisputeStatus,
+    evidence: Vec<String>,
+    closed_at: Option<DateTime<Utc>>,
+}
+
+impl Dispute {
+    /// Opens a dispute over `amount` of the transaction behind `payables`, which must be
+    /// every payable of that transaction. The amount cannot exceed what was captured and
+    /// neither refunded nor charged back since. `reason_code` is the code as sent by the card network, e.g.
+    /// `"10.4"`, and `respond_by` the last day to submit evidence.
+    pub fn open(
+        payables: &mut [Payable],
+        amount: Money,
+        reason: DisputeReason,
+        reason_code: impl Into<String>,
+        respond_by: NaiveDate,
+        clock: &impl Clock,
+    ) -> Result<Dispute, DisputeError> {
+        let first = payables.first().ok_or(DisputeError::NothingToDispute)?;
+        let tx = first.transaction();
+        if payables.iter().any(|payable| payable.transaction_id() != tx.id()) {
+            return Err(DisputeError::MixedTransactions);
+        }
+        if amount.checked_cmp(&Money::zero(tx.currency()))? != Ordering::Greater {
+            return Err(DisputeError::InvalidAmount(amount));
+        }
+        let refunded = Money::sum(payables.iter().map(Payable::refunded), tx.currency())?;
+        let chargedback = Money::sum(payables.iter().map(Payable::chargedback), tx.currency())?;
+        let disputable = tx.captured().checked_sub(refunded)?.checked_sub(chargedback)?;
+        if amount.checked_cmp(&disputable)? == Ordering::Greater {
+            return Err(DisputeError::ExceedsDisputable { amount, disputable });
+        }
+
+        let dispute = Dispute {
+            transaction_id: tx.id(),
+            merchant_id: tx.merchant_id().to_owned(),
+            amount,
+            refunded_at_open: refunded,
+            reason,
+            reason_code: reason_code.into(),
+            opened_at: clock.now(),
+            respond_by,
+            status: DisputeStatus::Opened,
+            evidence: Vec::new(),
+            closed_at: None,
+        };
+        for payable in payables.iter_mut().filter(|payable| *payable.status() == PayableStatus::WaitingFunds) {
+            payable.set_on_hold(true);
+        }
+        Ok(dispute)
+    }
+
+    pub fn transaction_id(&self) -> TransactionId {
+        self.transaction_id
+    }
+
+    pub fn merchant_id(&self) -> &str {
+        &self.merchant_id
+    }
+
+    pub fn amount(&self) -> Money {
+        self.amount
+    }
+
+    pub fn reason(&self) -> DisputeReason {
+        self.reason
+    }
+
+    /// The reason code as sent by the card network.
+    pub fn reason_code(&self) -> &str {
+        &self.reason_code
+    }
+
+    pub fn opened_at(&self) -> DateTime<Utc> {
+        self.opened_at
+    }
+
+    /// The last day the merchant can submit evidence.
+    pub fn respond_by(&self) -> NaiveDate {
+        self.respond_by
+    }
+
+    pub fn status(&self) -> DisputeStatus {
+        self.status
+    }
+
+    pub fn evidence(&self) -> &[String] {
+        &self.evidence
+    }
+
+    /// When the dispute was won or lost, if it has been.
+    pub fn closed_at(&self) -> Option<DateTime<Utc>> {
+        self.closed_at
+    }
+
+    /// Adds a piece of evidence, such as a delivery receipt, to send to the acquirer. More
+    /// can be added until the end of the deadline in `merchant`'s timezone as long as the
+    /// dispute is not closed.
+    pub fn submit_evidence(&mut self, evidence: impl Into<String>, merchant: &Merchant, clock: &impl Clock) -> Result<(), DisputeError> {
+        self.check_merchant(merchant)?;
+        if self.status != DisputeStatus::EvidenceSubmitted {
+            self.check_transition(DisputeStatus::EvidenceSubmitted)?;
+        }
+        if merchant.local_date(clock.now()) > self.respond_by {
+            return Err(DisputeError::DeadlinePassed { respond_by: self.respond_by });
+        }
+        self.evidence.push(evidence.into());
+        self.status = DisputeStatus::EvidenceSubmitted;
+        Ok(())
+    }
+
+    /// Closes the dispute in the merchant's favour and releases the hold on `payables`.
+    pub fn win(&mut self, payables: &mut [Payable], clock: &impl Clock) -> Result<(), DisputeError> {
+        self.close(DisputeStatus::Won, payables, clock)
+    }
+
+    /// Closes the dispute in the cardholder's favour, charging back the disputed amount of
+    /// `payables`. Funds still waiting are never paid out: those the amount covers in full,
+    /// from the last installment, are charged back as they stand. Whatever is left, plus the
+    /// chargeback fee `fees` charges on the disputed amount, is debited from `merchant`
+    /// through the returned payable, due today; paid payables it covers in full are charged
+    /// back too, and what is debited beyond them is recorded against the payables it comes
+    /// out of. Whatever was refunded since the dispute was opened already went back to the
+    /// cardholder and is not charged back again. The hold on the rest is released.
+    pub fn lose(&mut self, payables: &mut [Payable], fees: &impl FeePolicy, merchant: &Merchant, clock: &impl Clock) -> Result<Payable, DisputeError> {
+        self.check_merchant(merchant)?;
+        self.check_transition(DisputeStatus::Lost)?;
+        let tx = payables
+            .iter()
+            .find(|payable| payable.transaction_id() == self.transaction_id)
+            .map(|payable| payable.transaction().clone())
+            .ok_or(DisputeError::NothingToDispute)?;
+        let fee = fees.fee_for(self.amount)?;
+        let now = clock.now();
+        let today = merchant.local_date(now);
+
+        let refunded = payables.iter().filter(|payable| payable.transaction_id() == self.transaction_id).map(Payable::refunded);
+        let refunded_since = Money::sum(refunded, self.amount.currency())?.checked_sub(self.refunded_at_open)?;
+        let mut debit = match self.amount.checked_cmp(&refunded_since)? {
+            Ordering::Greater => self.amount.checked_sub(refunded_since)?,
+            _ => Money::zero(self.amount.currency()),
+        };
+        let mut left = debit;
+        for status in [PayableStatus::WaitingFunds, PayableStatus::Paid] {
+            let covered = payables.iter_mut().rev().filter(|payable| payable.transaction_id() == self.transaction_id && *payable.status() == status);
+            for payable in covered {
+                let outstanding = payable.outstanding()?;
+                if outstanding.amount() <= 0 || outstanding.checked_cmp(&left)? == Ordering::Greater {
+                    continue;
+                }
+                payable
+                    .transition_to(PayableStatus::Chargedback, now)
+                    .expect("funds waiting or paid can always be charged back");
+                payable.record_chargeback(outstanding)?;
+                left = left.checked_sub(outstanding)?;
+                if status == PayableStatus::WaitingFunds {
+                    debit = debit.checked_sub(outstanding)?;
+                }
+            }
+        }
+        // what no payable covers in full is debited, and recorded against the payables it
+        // comes out of so it cannot be refunded or disputed again
+        let partly_covered = payables.iter_mut().rev().filter(|payable| payable.transaction_id() == self.transaction_id);
+        for payable in partly_covered {
+            let outstanding = payable.outstanding()?;
+            if left.is_zero() || outstanding.amount() <= 0 {
+                continue;
+            }
+            let taken = if left.checked_cmp(&outstanding)? == Ordering::Less { left } else { outstanding };
+            payable.record_chargeback(taken)?;
+            left = left.checked_sub(taken)?;
+        }
+        let chargeback = Payable::new(PayableStatus::WaitingFunds, tx, Installment::SINGLE, debit.checked_neg()?, fee, today, None)?;
+        self.close(DisputeStatus::Lost, payables, clock)?;
+        Ok(chargeback)
+    }
+
+    fn close(&mut self, to: DisputeStatus, payables: &mut [Payable], clock: &impl Clock) -> Result<(), DisputeError> {
+        self.check_transition(to)?;
+        for payable in payables.iter_mut().filter(|payable| payable.transaction_id() == self.transaction_id) {
+            payable.set_on_hold(false);
+        }
+        self.status = to;
+        self.closed_at = Some(clock.now());
+        Ok(())
+    }
+
+    fn check_merchant(&self, merchant: &Merchant) -> Result<(), DisputeError> {
+        if merchant.id() != self.merchant_id {
+            return Err(DisputeError::MerchantMismatch { expected: merchant.id().to_owned(), found: self.merchant_id.clone() });
+        }
+        Ok(())
+    }
+
+    fn check_transition(&self, to: DisputeStatus) -> Result<(), DisputeError> {
+        if !self.status.can_transition_to(to) {
+            return Err(DisputeError::IllegalTransition { from: self.status, to });
+        }
+        Ok(())
+    }
+}
+
+/// What a dispute is serialized as. Deserializing checks that the amounts are in one
+/// currency and that the evidence and closing time match the status, the way `open`,
+/// `submit_evidence` and closing the dispute would have left them.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct DisputeRecord {
+    transaction_id: TransactionId,
+    merchant_id: String,
+    amount: Money,
+    refunded_at_open: Money,
+    reason: DisputeReason,
+    reason_code: String,
+    opened_at: DateTime<Utc>,
+    respond_by: NaiveDate,
+    status: DisputeStatus,
+    evidence: Vec<String>,
+    closed_at: Option<DateTime<Utc>>,
+}
+
+#[cfg(feature = "serde")]
+impl From<Dispute> for DisputeRecord {
+    fn from(dispute: Dispute) -> Self {
+        DisputeRecord {
+            transaction_id: dispute.transaction_id,
+            merchant_id: dispute.merchant_id,
+            amount: dispute.amount,
+            refunded_at_open: dispute.refunded_at_open,
+            reason: dispute.reason,
+            reason_code: dispute.reason_code,
+            opened_at: dispute.opened_at,
+            respond_by: dispute.respond_by,
+            status: dispute.status,
+            evidence: dispute.evidence,
+            closed_at: dispute.closed_at,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<DisputeRecord> for Dispute {
+    type Error = DisputeError;
+
+    fn try_from(record: DisputeRecord) -> Result<Self, Self::Error> {
+        if record.amount.checked_cmp(&Money::zero(record.amount.currency()))? != Ordering::Greater {
+            return Err(DisputeError::InvalidAmount(record.amount));
+        }
+        // refunds are never negative, and comparing also checks the currency
+        if record.refunded_at_open.checked_cmp(&Money::zero(record.amount.currency()))? == Ordering::Less {
+            return Err(DisputeError::InvalidAmount(record.refunded_at_open));
+        }
+        let consistent = match record.status {
+            DisputeStatus::Opened => record.evidence.is_empty() && record.closed_at.is_none(),
+            DisputeStatus::EvidenceSubmitted => !record.evidence.is_empty() && record.closed_at.is_none(),
+            _ => record.closed_at.is_some_and(|closed_at| closed_at >= record.opened_at),
+        };
+        if !consistent {
+            return Err(DisputeError::InconsistentStatus { status: record.status });
+        }
+        Ok(Dispute {
+            transaction_id: record.transaction_id,
+            merchant_id: record.merchant_id,
+            amount: record.amount,
+            refunded_at_open: record.refunded_at_open,
+            reason: record.reason,
+            reason_code: record.reason_code,
+            opened_at: record.opened_at,
+            respond_by: record.respond_by,
+            status: record.status,
+            evidence: record.evidence,
+            closed_at: record.closed_at,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FixedClock;
+    use crate::fees::FlatFee;
+    use crate::money::Currency;
+    use crate::refund::{FeeRefund, Refunder};
+    use crate::settlement::{settle_due_payables, InMemoryPayableStore, PayableStore};
+    use crate::test_support::{brl, clock_on, date, installments, merchant, merchant_named, payables};
+    use crate::transaction::PaymentMethod;
+    use chrono::TimeZone;
+
+    fn open(payables: &mut [Payable]) -> Dispute {
+        Dispute::open(payables, brl(10000), DisputeReason::NotReceived, "13.1", date(2024, 4, 30), &clock_on(2024, 4, 10)).unwrap()
+    }
+
+    #[test]
+    fn should_hold_pending_payables_until_the_dispute_is_won() {
+        let mut payables = installments();
+        let mut dispute = open(&mut payables);
+        let mut store = InMemoryPayableStore::new();
+        store.add(&merchant(), payables);
+
+        let report = settle_due_payables(&mut store, &clock_on(2024, 4, 15)).unwrap();
+        assert_eq!(report.merchants, vec![]);
+
+        dispute.submit_evidence("tracking code BR123456789", &merchant(), &clock_on(2024, 4, 20)).unwrap();
+        dispute.win(store.payables_mut("merchant-1"), &clock_on(2024, 4, 25)).unwrap();
+
+        assert_eq!(dispute.status(), DisputeStatus::Won);
+        assert_eq!(dispute.evidence(), ["tracking code BR123456789"]);
+        let report = settle_due_payables(&mut store, &clock_on(2024, 4, 25)).unwrap();
+        assert_eq!(report.for_merchant("merchant-1", Currency::BRL).map(|settlement| settlement.net), Some(brl(4750)));
+    }
+
+    #[test]
+    fn should_charge_back_pending_payables_instead_of_paying_them_out() {
+        let mut payables = installments();
+        let mut dispute = open(&mut payables);
+
+        let chargeback = dispute.lose(&mut payables, &FlatFee::new(brl(1500)), &merchant(), &clock_on(2024, 5, 2)).unwrap();
+
+        assert!(payables.iter().all(|payable| *payable.status() == PayableStatus::Chargedback));
+        assert_eq!((chargeback.gross(), chargeback.fee(), chargeback.net()), (brl(0), brl(1500), brl(-1500)));
+        assert_eq!(chargeback.due_date(), date(2024, 5, 2));
+        assert_eq!(chargeback.transaction_id(), dispute.transaction_id());
+        assert_eq!(dispute.closed_at(), Some(Utc.with_ymd_and_hms(2024, 5, 2, 12, 0, 0).unwrap()));
+    }
+
+    #[test]
+    fn should_debit_what_was_already_paid_out_and_the_chargeback_fee_when_lost() {
+        let mut payables = installments();
+        payables[0].settle(date(2024, 4, 15), Utc.with_ymd_and_hms(2024, 4, 15, 12, 0, 0).unwrap()).unwrap();
+        let mut dispute = open(&mut payables);
+
+        let chargeback = dispute.lose(&mut payables, &FlatFee::new(brl(1500)), &merchant(), &clock_on(2024, 5, 2)).unwrap();
+
+        assert!(payables.iter().all(|payable| *payable.status() == PayableStatus::Chargedback && !payable.is_on_hold()));
+        assert_eq!((chargeback.gross(), chargeback.fee(), chargeback.net()), (brl(-5000), brl(1500), brl(-6500)));
+    }
+
+    #[test]
+    fn should_refuse_evidence_after_the_deadline_or_once_closed() {
+        let mut payables = installments();
+        let mut dispute = open(&mut payables);
+
+        // 21:30 on the last day in São Paulo is still on time
+        dispute.submit_evidence("receipt", &merchant(), &FixedClock::new(Utc.with_ymd_and_hms(2024, 5, 1, 0, 30, 0).unwrap())).unwrap();
+        assert_eq!(
+            dispute.submit_evidence("receipt", &merchant(), &clock_on(2024, 5, 1)).unwrap_err(),
+            DisputeError::DeadlinePassed { respond_by: date(2024, 4, 30) }
+        );
+        dispute.win(&mut payables, &clock_on(2024, 5, 2)).unwrap();
+        assert_eq!(
+            dispute.submit_evidence("receipt", &merchant(), &clock_on(2024, 4, 20)).unwrap_err(),
+            DisputeError::IllegalTransition { from: DisputeStatus::Won, to: DisputeStatus::EvidenceSubmitted }
+        );
+        assert!(dispute.lose(&mut payables, &FlatFee::new(brl(1500)), &merchant(), &clock_on(2024, 5, 2)).is_err());
+    }
+
+    #[test]
+    fn should_only_let_the_merchant_of_the_transaction_answer_the_dispute() {
+        let mut payables = installments();
+        let mut dispute = open(&mut payables);
+        let other = merchant_named("merchant-2");
+        let mismatch = DisputeError::MerchantMismatch { expected: "merchant-2".to_owned(), found: "merchant-1".to_owned() };
+
+        assert_eq!(dispute.submit_evidence("receipt", &other, &clock_on(2024, 4, 20)).unwrap_err(), mismatch);
+        assert_eq!(dispute.lose(&mut payables, &FlatFee::new(brl(1500)), &other, &clock_on(2024, 5, 2)).unwrap_err(), mismatch);
+        assert!(payables.iter().all(|payable| *payable.status() == PayableStatus::WaitingFunds));
+    }
+
+    #[test]
+    fn should_refuse_to_dispute_more_than_was_captured_and_not_refunded() {
+        let mut payables = installments();
+
+        let result = Dispute::open(&mut payables, brl(10001), DisputeReason::Fraud, "10.4", date(2024, 4, 30), &clock_on(2024, 4, 10));
+        assert_eq!(result.unwrap_err(), DisputeError::ExceedsDisputable { amount: brl(10001), disputable: brl(10000) });
+        assert!(payables.iter().all(|payable| !payable.is_on_hold()));
+
+        Refunder::new(FeeRefund::Proportional).refund(&mut payables, brl(4000), &merchant(), &clock_on(2024, 4, 5)).unwrap();
+        let result = Dispute::open(&mut payables, brl(10000), DisputeReason::Fraud, "10.4", date(2024, 4, 30), &clock_on(2024, 4, 10));
+        assert_eq!(result.unwrap_err(), DisputeError::ExceedsDisputable { amount: brl(10000), disputable: brl(6000) });
+    }
+
+    #[test]
+    fn should_not_charge_back_what_was_refunded_while_the_dispute_was_open() {
+        let mut payables = installments();
+        let mut dispute = open(&mut payables);
+
+        let refund = Refunder::new(FeeRefund::Proportional).refund(&mut payables, brl(4000), &merchant(), &clock_on(2024, 4, 12)).unwrap();
+        let chargeback = dispute.lose(&mut payables, &FlatFee::new(brl(1500)), &merchant(), &clock_on(2024, 5, 2)).unwrap();
+
+        assert!(payables.iter().all(|payable| *payable.status() == PayableStatus::Chargedback));
+        let chargedback = Money::sum(payables.iter().map(Payable::gross), Currency::BRL).unwrap();
+        assert_eq!(refund.amount.checked_add(chargedback), Ok(brl(10000)));
+        assert_eq!((chargeback.gross(), chargeback.net()), (brl(0), brl(-1500)));
+    }
+
+    #[test]
+    fn should_refuse_to_dispute_again_what_was_already_charged_back() {
+        let mut payables = installments();
+        payables[0].settle(date(2024, 4, 15), Utc.with_ymd_and_hms(2024, 4, 15, 12, 0, 0).unwrap()).unwrap();
+        let mut dispute = open(&mut payables);
+        dispute.lose(&mut payables, &FlatFee::new(brl(1500)), &merchant(), &clock_on(2024, 5, 2)).unwrap();
+
+        let result = Dispute::open(&mut payables, brl(10000), DisputeReason::Fraud, "10.4", date(2024, 5, 30), &clock_on(2024, 5, 10));
+
+        assert_eq!(result.unwrap_err(), DisputeError::ExceedsDisputable { amount: brl(10000), disputable: brl(0) });
+    }
+
+    #[test]
+    fn should_count_a_partial_chargeback_against_later_disputes_and_refunds() {
+        let mut payables = payables(&merchant(), PaymentMethod::Debit, brl(10000), 1);
+        let fees = FlatFee::new(brl(0));
+        let mut first = Dispute::open(&mut payables, brl(3000), DisputeReason::NotAsDescribed, "13.3", date(2024, 4, 30), &clock_on(2024, 4, 10)).unwrap();
+        let chargeback = first.lose(&mut payables, &fees, &merchant(), &clock_on(2024, 5, 2)).unwrap();
+
+        assert_eq!((chargeback.gross(), payables[0].chargedback()), (brl(-3000), brl(3000)));
+        assert_eq!(Refunder::new(FeeRefund::Proportional).remaining(&payables), Ok(brl(7000)));
+        let result = Dispute::open(&mut payables, brl(10000), DisputeReason::Fraud, "10.4", date(2024, 5, 30), &clock_on(2024, 5, 10));
+        assert_eq!(result.unwrap_err(), DisputeError::ExceedsDisputable { amount: brl(10000), disputable: brl(7000) });
+
+        let mut second = Dispute::open(&mut payables, brl(7000), DisputeReason::Fraud, "10.4", date(2024, 5, 30), &clock_on(2024, 5, 10)).unwrap();
+        let chargeback = second.lose(&mut payables, &fees, &merchant(), &clock_on(2024, 6, 3)).unwrap();
+
+        assert_eq!(chargeback.gross(), brl(-7000));
+        assert_eq!((*payables[0].status(), payables[0].chargedback()), (PayableStatus::Chargedback, brl(10000)));
+        assert_eq!(Refunder::new(FeeRefund::Proportional).remaining(&payables), Ok(brl(0)));
+    }
+}
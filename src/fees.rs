@@ -0,0 +1,263 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use crate::money::{Money, MoneyError, Rate, RoundingMode};
+use crate::transaction::PaymentMethod;
+
+pub const DEFAULT_FEE_FOR_DEBIT: Rate = Rate::from_basis_points(300);
+pub const DEFAULT_FEE_FOR_CREDIT: Rate = Rate::from_basis_points(500);
+
+/// Decides how much the PSP keeps out of a transaction value.
+pub trait FeePolicy {
+    fn fee_for(&self, value: Money) -> Result<Money, MoneyError>;
+}
+
+/// The same fee regardless of the transaction value.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct FlatFee {
+    fee: Money,
+}
+
+impl FlatFee {
+    pub fn new(fee: Money) -> Self {
+        FlatFee { fee }
+    }
+}
+
+impl FeePolicy for FlatFee {
+    fn fee_for(&self, value: Money) -> Result<Money, MoneyError> {
+        Money::zero(value.currency()).checked_add(self.fee)
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct PercentageFee {
+    rate: Rate,
+    rounding: RoundingMode,
+}
+
+impl PercentageFee {
+    pub fn new(rate: Rate) -> Self {
+        PercentageFee { rate, rounding: RoundingMode::default() }
+    }
+
+    pub fn with_rounding(mut self, rounding: RoundingMode) -> Self {
+        self.rounding = rounding;
+        self
+    }
+}
+
+impl FeePolicy for PercentageFee {
+    fn fee_for(&self, value: Money) -> Result<Money, MoneyError> {
+        value.apply_rate(self.rate, self.rounding)
+    }
+}
+
+/// A percentage of the value plus a fixed amount per transaction, e.g. 2.99% + R$ 0.39.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct PercentagePlusFixedFee {
+    percentage: PercentageFee,
+    fixed: FlatFee,
+}
+
+impl PercentagePlusFixedFee {
+    pub fn new(rate: Rate, fixed: Money) -> Self {
+        PercentagePlusFixedFee { percentage: PercentageFee::new(rate), fixed: FlatFee::new(fixed) }
+    }
+
+    pub fn with_rounding(mut self, rounding: RoundingMode) -> Self {
+        self.percentage = self.percentage.with_rounding(rounding);
+        self
+    }
+}
+
+impl FeePolicy for PercentagePlusFixedFee {
+    fn fee_for(&self, value: Money) -> Result<Money, MoneyError> {
+        self.percentage.fee_for(value)?.checked_add(self.fixed.fee_for(value)?)
+    }
+}
+
+/// Picks a different policy depending on the transaction value. Each tier applies from its
+/// threshold upwards, and values below every threshold fall back to the base policy.
+pub struct TieredFee {
+    base: Box<dyn FeePolicy>,
+    tiers: Vec<(Money, Box<dyn FeePolicy>)>,
+}
+
+impl TieredFee {
+    pub fn new(base: impl FeePolicy + 'static) -> Self {
+        TieredFee { base: Box::new(base), tiers: Vec::new() }
+    }
+
+    pub fn with_tier(mut self, from: Money, policy: impl FeePolicy + 'static) -> Self {
+        let position = self
+            .tiers
+            .iter()
+            .position(|(threshold, _)| threshold.amount() > from.amount())
+            .unwrap_or(self.tiers.len());
+        self.tiers.insert(position, (from, Box::new(policy)));
+        self
+    }
+}
+
+impl FeePolicy for TieredFee {
+    fn fee_for(&self, value: Money) -> Result<Money, MoneyError> {
+        let mut policy = &self.base;
+        for (threshold, tier) in &self.tiers {
+            if value.checked_cmp(threshold)? == Ordering::Less {
+                break;
+            }
+            policy = tier;
+        }
+        policy.fee_for(value)
+    }
+}
+
+/// Keeps the fee of another policy between a minimum and a maximum.
+pub struct BoundedFee {
+    inner: Box<dyn FeePolicy>,
+    minimum: Option<Money>,
+    maximum: Option<Money>,
+}
+
+impl BoundedFee {
+    pub fn new(inner: impl FeePolicy + 'static) -> Self {
+        BoundedFee { inner: Box::new(inner), minimum: None, maximum: None }
+    }
+
+    pub fn with_minimum(mut self, minimum: Money) -> Self {
+        self.minimum = Some(minimum);
+        self
+    }
+
+    pub fn with_maximum(mut self, maximum: Money) -> Self {
+        self.maximum = Some(maximum);
+        self
+    }
+}
+
+impl FeePolicy for BoundedFee {
+    fn fee_for(&self, value: Money) -> Result<Money, MoneyError> {
+        let mut fee = self.inner.fee_for(value)?;
+        if let Some(minimum) = self.minimum {
+            if fee.checked_cmp(&minimum)? == Ordering::Less {
+                fee = minimum;
+            }
+        }
+        if let Some(maximum) = self.maximum {
+            if fee.checked_cmp(&maximum)? == Ordering::Greater {
+                fee = maximum;
+            }
+        }
+        Ok(fee)
+    }
+}
+
+/// The fee policy to use for each payment method.
+pub struct FeeSchedule {
+    debit: Box<dyn FeePolicy>,
+    credit: Box<dyn FeePolicy>,
+}
+
+impl FeeSchedule {
+    pub fn new(debit: impl FeePolicy + 'static, credit: impl FeePolicy + 'static) -> Self {
+        FeeSchedule { debit: Box::new(debit), credit: Box::new(credit) }
+    }
+
+    pub fn policy_for(&self, method: &PaymentMethod) -> &dyn FeePolicy {
+        match method {
+            PaymentMethod::Debit => self.debit.as_ref(),
+            PaymentMethod::Credit => self.credit.as_ref(),
+        }
+    }
+}
+
+impl Default for FeeSchedule {
+    fn default() -> Self {
+        FeeSchedule::new(PercentageFee::new(DEFAULT_FEE_FOR_DEBIT), PercentageFee::new(DEFAULT_FEE_FOR_CREDIT))
+    }
+}
+
+/// A default fee schedule plus the rates negotiated by individual merchants.
+#[derive(Default)]
+pub struct FeeTable {
+    default: FeeSchedule,
+    merchants: HashMap<String, FeeSchedule>,
+}
+
+impl FeeTable {
+    pub fn new(default: FeeSchedule) -> Self {
+        FeeTable { default, merchants: HashMap::new() }
+    }
+
+    pub fn with_override(mut self, merchant_id: impl Into<String>, schedule: FeeSchedule) -> Self {
+        self.merchants.insert(merchant_id.into(), schedule);
+        self
+    }
+
+    pub fn schedule_for(&self, merchant_id: &str) -> &FeeSchedule {
+        self.merchants.get(merchant_id).unwrap_or(&self.default)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::money::Currency;
+    use crate::test_support::brl;
+
+    #[test]
+    fn should_charge_a_flat_fee_in_the_currency_of_the_value() {
+        let policy = FlatFee::new(brl(39));
+        assert_eq!(policy.fee_for(brl(10000)), Ok(brl(39)));
+        assert_eq!(
+            policy.fee_for(Money::from_minor(10000, Currency::USD)),
+            Err(MoneyError::CurrencyMismatch { expected: Currency::USD, found: Currency::BRL })
+        );
+    }
+
+    #[test]
+    fn should_charge_a_percentage_plus_a_fixed_amount() {
+        let policy = PercentagePlusFixedFee::new(Rate::from_basis_points(299), brl(39));
+        // 2.99% of 100.00 is 2.99, plus 0.39
+        assert_eq!(policy.fee_for(brl(10000)), Ok(brl(338)));
+    }
+
+    #[test]
+    fn should_pick_the_tier_matching_the_value() {
+        let policy = TieredFee::new(PercentageFee::new(Rate::from_basis_points(500)))
+            .with_tier(brl(100000), PercentageFee::new(Rate::from_basis_points(300)))
+            .with_tier(brl(50000), PercentageFee::new(Rate::from_basis_points(400)));
+
+        assert_eq!(policy.fee_for(brl(10000)), Ok(brl(500)));
+        assert_eq!(policy.fee_for(brl(50000)), Ok(brl(2000)));
+        assert_eq!(policy.fee_for(brl(200000)), Ok(brl(6000)));
+    }
+
+    #[test]
+    fn should_keep_the_fee_between_the_minimum_and_the_maximum() {
+        let policy = BoundedFee::new(PercentageFee::new(Rate::from_basis_points(300)))
+            .with_minimum(brl(50))
+            .with_maximum(brl(1000));
+
+        assert_eq!(policy.fee_for(brl(1000)), Ok(brl(50)));
+        assert_eq!(policy.fee_for(brl(10000)), Ok(brl(300)));
+        assert_eq!(policy.fee_for(brl(100000)), Ok(brl(1000)));
+    }
+
+    #[test]
+    fn should_fall_back_to_the_default_schedule_for_merchants_without_an_override() {
+        let table = FeeTable::default().with_override(
+            "merchant-with-deal",
+            FeeSchedule::new(PercentageFee::new(Rate::from_basis_points(150)), FlatFee::new(brl(100))),
+        );
+
+        let negotiated = table.schedule_for("merchant-with-deal");
+        assert_eq!(negotiated.policy_for(&PaymentMethod::Debit).fee_for(brl(10000)), Ok(brl(150)));
+        assert_eq!(negotiated.policy_for(&PaymentMethod::Credit).fee_for(brl(10000)), Ok(brl(100)));
+
+        let default = table.schedule_for("anyone-else");
+        assert_eq!(default.policy_for(&PaymentMethod::Debit).fee_for(brl(10000)), Ok(brl(300)));
+        assert_eq!(default.policy_for(&PaymentMethod::Credit).fee_for(brl(10000)), Ok(brl(500)));
+    }
+}
@@ -0,0 +1,76 @@
+use std::fmt;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use chrono_tz::Tz;
+
+#[derive(PartialEq, Debug)]
+#[non_exhaustive]
+pub enum MerchantError {
+    UnknownTimezone(String),
+}
+
+impl fmt::Display for MerchantError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MerchantError::UnknownTimezone(name) => write!(f, "unknown IANA timezone {name:?}"),
+        }
+    }
+}
+
+impl std::error::Error for MerchantError {}
+
+/// A merchant and the timezone its business days are counted in.
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Merchant {
+    id: String,
+    timezone: Tz,
+}
+
+impl Merchant {
+    pub fn new(id: impl Into<String>, timezone: Tz) -> Self {
+        Merchant { id: id.into(), timezone }
+    }
+
+    /// Builds a merchant from an IANA timezone name such as `"America/Sao_Paulo"`.
+    pub fn with_timezone_name(id: impl Into<String>, timezone: &str) -> Result<Self, MerchantError> {
+        let timezone = timezone.parse().map_err(|_| MerchantError::UnknownTimezone(timezone.to_owned()))?;
+        Ok(Merchant::new(id, timezone))
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn timezone(&self) -> Tz {
+        self.timezone
+    }
+
+    /// The calendar date `instant` falls on for this merchant.
+    pub fn local_date(&self, instant: DateTime<Utc>) -> NaiveDate {
+        instant.with_timezone(&self.timezone).date_naive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn should_date_an_instant_in_the_merchant_timezone() {
+        let merchant = Merchant::with_timezone_name("merchant-1", "America/Sao_Paulo").unwrap();
+        // 23:30 in São Paulo is already the next day in UTC
+        let instant = Utc.with_ymd_and_hms(2024, 3, 16, 2, 30, 0).unwrap();
+
+        assert_eq!(merchant.local_date(instant), NaiveDate::from_ymd_opt(2024, 3, 15).unwrap());
+    }
+
+    #[test]
+    fn should_reject_unknown_timezones() {
+        assert_eq!(
+            Merchant::with_timezone_name("merchant-1", "America/Atlantis"),
+            Err(MerchantError::UnknownTimezone("America/Atlantis".to_owned()))
+        );
+    }
+}
@@ -0,0 +1,444 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+/// ISO 4217 currencies the crate knows how to settle.
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Currency {
+    ARS,
+    BHD,
+    BRL,
+    CLP,
+    EUR,
+    GBP,
+    JPY,
+    KWD,
+    MXN,
+    USD,
+}
+
+impl Currency {
+    pub const ALL: [Currency; 10] = [
+        Currency::ARS,
+        Currency::BHD,
+        Currency::BRL,
+        Currency::CLP,
+        Currency::EUR,
+        Currency::GBP,
+        Currency::JPY,
+        Currency::KWD,
+        Currency::MXN,
+        Currency::USD,
+    ];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Currency::ARS => "ARS",
+            Currency::BHD => "BHD",
+            Currency::BRL => "BRL",
+            Currency::CLP => "CLP",
+            Currency::EUR => "EUR",
+            Currency::GBP => "GBP",
+            Currency::JPY => "JPY",
+            Currency::KWD => "KWD",
+            Currency::MXN => "MXN",
+            Currency::USD => "USD",
+        }
+    }
+
+    pub fn numeric_code(&self) -> u16 {
+        match self {
+            Currency::ARS => 32,
+            Currency::BHD => 48,
+            Currency::BRL => 986,
+            Currency::CLP => 152,
+            Currency::EUR => 978,
+            Currency::GBP => 826,
+            Currency::JPY => 392,
+            Currency::KWD => 414,
+            Currency::MXN => 484,
+            Currency::USD => 840,
+        }
+    }
+
+    /// Number of decimal digits between the major and the minor unit.
+    pub fn minor_unit(&self) -> u32 {
+        match self {
+            Currency::CLP | Currency::JPY => 0,
+            Currency::ARS | Currency::BRL | Currency::EUR | Currency::GBP | Currency::MXN | Currency::USD => 2,
+            Currency::BHD | Currency::KWD => 3,
+        }
+    }
+
+    fn minor_per_major(&self) -> i64 {
+        10_i64.pow(self.minor_unit())
+    }
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+impl FromStr for Currency {
+    type Err = MoneyError;
+
+    fn from_str(code: &str) -> Result<Self, Self::Err> {
+        Currency::ALL
+            .into_iter()
+            .find(|currency| currency.code().eq_ignore_ascii_case(code))
+            .ok_or_else(|| MoneyError::UnknownCurrency(code.to_owned()))
+    }
+}
+
+/// How to get rid of the fraction of a minor unit left over by a division.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
+#[non_exhaustive]
+pub enum RoundingMode {
+    /// Ties go to the even neighbour (banker's rounding).
+    #[default]
+    HalfEven,
+    /// Ties go away from zero.
+    HalfUp,
+    /// The fraction is dropped.
+    Truncate,
+}
+
+impl RoundingMode {
+    fn divide(&self, dividend: i128, divisor: i128) -> i128 {
+        let quotient = dividend / divisor;
+        let remainder = dividend % divisor;
+        if remainder == 0 {
+            return quotient;
+        }
+        let away_from_zero = if (dividend < 0) == (divisor < 0) { quotient + 1 } else { quotient - 1 };
+        let twice_remainder = remainder.abs() * 2;
+        match self {
+            RoundingMode::Truncate => quotient,
+            RoundingMode::HalfUp if twice_remainder >= divisor.abs() => away_from_zero,
+            RoundingMode::HalfEven if twice_remainder > divisor.abs() => away_from_zero,
+            RoundingMode::HalfEven if twice_remainder == divisor.abs() && quotient % 2 != 0 => away_from_zero,
+            _ => quotient,
+        }
+    }
+}
+
+#[derive(PartialEq, Debug)]
+#[non_exhaustive]
+pub enum MoneyError {
+    Overflow,
+    DivisionByZero,
+    CurrencyMismatch { expected: Currency, found: Currency },
+    InvalidAmount(String),
+    UnknownCurrency(String),
+}
+
+impl fmt::Display for MoneyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MoneyError::Overflow => f.write_str("amount overflowed"),
+            MoneyError::DivisionByZero => f.write_str("cannot divide an amount by zero"),
+            MoneyError::CurrencyMismatch { expected, found } => {
+                write!(f, "expected an amount in {expected}, found {found}")
+            }
+            MoneyError::InvalidAmount(amount) => write!(f, "invalid amount {amount:?}"),
+            MoneyError::UnknownCurrency(code) => write!(f, "unknown ISO 4217 currency {code:?}"),
+        }
+    }
+}
+
+impl std::error::Error for MoneyError {}
+
+/// A percentage expressed in basis points, so 3% is `Rate::from_basis_points(300)`.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rate {
+    basis_points: u32,
+}
+
+impl Rate {
+    const BASIS_POINTS_PER_UNIT: i128 = 10_000;
+
+    pub const fn from_basis_points(basis_points: u32) -> Self {
+        Rate { basis_points }
+    }
+
+    pub fn basis_points(&self) -> u32 {
+        self.basis_points
+    }
+}
+
+/// An exact amount of money, kept as an integer number of minor units (cents for BRL).
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Money {
+    amount: i64,
+    currency: Currency,
+}
+
+impl Money {
+    pub fn from_minor(amount: i64, currency: Currency) -> Self {
+        Money { amount, currency }
+    }
+
+    pub fn zero(currency: Currency) -> Self {
+        Money::from_minor(0, currency)
+    }
+
+    /// Parses a decimal string such as `"20.50"`, refusing more fractional digits than the
+    /// currency has instead of rounding them away.
+    pub fn parse(value: &str, currency: Currency) -> Result<Self, MoneyError> {
+        let invalid = || MoneyError::InvalidAmount(value.to_owned());
+        let (negative, digits) = match value.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, value),
+        };
+        let (major, minor) = digits.split_once('.').unwrap_or((digits, ""));
+        let minor_unit = currency.minor_unit() as usize;
+        let all_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+        if major.is_empty() || !all_digits(major) || !all_digits(minor) || minor.len() > minor_unit {
+            return Err(invalid());
+        }
+        if digits.contains('.') && minor.is_empty() {
+            return Err(invalid());
+        }
+
+        let major: i64 = major.parse().map_err(|_| MoneyError::Overflow)?;
+        let minor: i64 = format!("{minor:0<minor_unit$}").parse().unwrap_or(0);
+        let amount = major
+            .checked_mul(currency.minor_per_major())
+            .and_then(|amount| amount.checked_add(minor))
+            .ok_or(MoneyError::Overflow)?;
+        Ok(Money::from_minor(if negative { -amount } else { amount }, currency))
+    }
+
+    /// Adds up `amounts`, failing on the first one that is not in `currency` instead of
+    /// summing across currencies.
+    pub fn sum<I>(amounts: I, currency: Currency) -> Result<Money, MoneyError>
+    where
+        I: IntoIterator<Item = Money>,
+    {
+        amounts
+            .into_iter()
+            .try_fold(Money::zero(currency), |total, amount| total.checked_add(amount))
+    }
+
+    pub fn amount(&self) -> i64 {
+        self.amount
+    }
+
+    pub fn currency(&self) -> Currency {
+        self.currency
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.amount == 0
+    }
+
+    /// Orders two amounts, which is only meaningful when they share a currency.
+    pub fn checked_cmp(&self, other: &Money) -> Result<Ordering, MoneyError> {
+        self.same_currency(other)?;
+        Ok(self.amount.cmp(&other.amount))
+    }
+
+    pub fn checked_add(&self, other: Money) -> Result<Money, MoneyError> {
+        self.same_currency(&other)?;
+        let amount = self.amount.checked_add(other.amount).ok_or(MoneyError::Overflow)?;
+        Ok(Money::from_minor(amount, self.currency))
+    }
+
+    pub fn checked_sub(&self, other: Money) -> Result<Money, MoneyError> {
+        self.same_currency(&other)?;
+        let amount = self.amount.checked_sub(other.amount).ok_or(MoneyError::Overflow)?;
+        Ok(Money::from_minor(amount, self.currency))
+    }
+
+    pub fn checked_mul(&self, factor: i64) -> Result<Money, MoneyError> {
+        let amount = self.amount.checked_mul(factor).ok_or(MoneyError::Overflow)?;
+        Ok(Money::from_minor(amount, self.currency))
+    }
+
+    /// Splits the amount into `parts` amounts that add up to it exactly, handing the minor
+    /// units left over by the division to the first parts, one each.
+    pub fn split(&self, parts: u32) -> Vec<Money> {
+        let parts = i64::from(parts);
+        if parts == 0 {
+            return Vec::new();
+        }
+        let share = self.amount / parts;
+        let leftover = self.amount % parts;
+        (0..parts)
+            .map(|part| {
+                let extra = if part < leftover.abs() { leftover.signum() } else { 0 };
+                Money::from_minor(share + extra, self.currency)
+            })
+            .collect()
+    }
+
+    /// Applies `rate` to the amount, rounding the result to the currency's minor unit.
+    pub fn apply_rate(&self, rate: Rate, rounding: RoundingMode) -> Result<Money, MoneyError> {
+        let product = self.amount as i128 * rate.basis_points as i128;
+        let amount = rounding.divide(product, Rate::BASIS_POINTS_PER_UNIT);
+        let amount = i64::try_from(amount).map_err(|_| MoneyError::Overflow)?;
+        Ok(Money::from_minor(amount, self.currency))
+    }
+
+    /// Applies `rate` scaled by `elapsed / period`, e.g. a monthly rate over 45 of 30 days.
+    pub fn apply_rate_pro_rata(&self, rate: Rate, elapsed: u32, period: u32, rounding: RoundingMode) -> Result<Money, MoneyError> {
+        if period == 0 {
+            return Err(MoneyError::DivisionByZero);
+        }
+        let product = self.amount as i128 * rate.basis_points as i128 * elapsed as i128;
+        let amount = rounding.divide(product, Rate::BASIS_POINTS_PER_UNIT * period as i128);
+        let amount = i64::try_from(amount).map_err(|_| MoneyError::Overflow)?;
+        Ok(Money::from_minor(amount, self.currency))
+    }
+
+    /// The `numerator / denominator` share of the amount, e.g. the part of a fee that goes
+    /// with 30.00 out of a 100.00 value.
+    pub fn scale(&self, numerator: i64, denominator: i64, rounding: RoundingMode) -> Result<Money, MoneyError> {
+        if denominator == 0 {
+            return Err(MoneyError::DivisionByZero);
+        }
+        let amount = rounding.divide(self.amount as i128 * numerator as i128, denominator as i128);
+        let amount = i64::try_from(amount).map_err(|_| MoneyError::Overflow)?;
+        Ok(Money::from_minor(amount, self.currency))
+    }
+
+    pub fn checked_neg(&self) -> Result<Money, MoneyError> {
+        let amount = self.amount.checked_neg().ok_or(MoneyError::Overflow)?;
+        Ok(Money::from_minor(amount, self.currency))
+    }
+
+    fn same_currency(&self, other: &Money) -> Result<(), MoneyError> {
+        if self.currency != other.currency {
+            return Err(MoneyError::CurrencyMismatch { expected: self.currency, found: other.currency });
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.amount < 0 { "-" } else { "" };
+        let minor_unit = self.currency.minor_unit() as usize;
+        let per_major = self.currency.minor_per_major().unsigned_abs();
+        let major = self.amount.unsigned_abs() / per_major;
+        let minor = self.amount.unsigned_abs() % per_major;
+        if minor_unit == 0 {
+            write!(f, "{} {sign}{major}", self.currency)
+        } else {
+            write!(f, "{} {sign}{major}.{minor:0minor_unit$}", self.currency)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn brl(amount: i64) -> Money {
+        Money::from_minor(amount, Currency::BRL)
+    }
+
+    #[test]
+    fn should_parse_decimal_amounts_into_minor_units() {
+        assert_eq!(Money::parse("20.50", Currency::BRL), Ok(brl(2050)));
+        assert_eq!(Money::parse("20.5", Currency::BRL), Ok(brl(2050)));
+        assert_eq!(Money::parse("7", Currency::BRL), Ok(brl(700)));
+        assert_eq!(Money::parse("-0.01", Currency::BRL), Ok(brl(-1)));
+        assert_eq!(Money::parse("20.505", Currency::BRL), Err(MoneyError::InvalidAmount("20.505".to_owned())));
+        assert_eq!(Money::parse("2O.50", Currency::BRL), Err(MoneyError::InvalidAmount("2O.50".to_owned())));
+    }
+
+    #[test]
+    fn should_round_a_three_percent_fee_according_to_the_rounding_mode() {
+        let rate = Rate::from_basis_points(300);
+        // 3% of 20.50 is 0.615
+        assert_eq!(brl(2050).apply_rate(rate, RoundingMode::HalfEven), Ok(brl(62)));
+        assert_eq!(brl(2050).apply_rate(rate, RoundingMode::HalfUp), Ok(brl(62)));
+        assert_eq!(brl(2050).apply_rate(rate, RoundingMode::Truncate), Ok(brl(61)));
+        // 3% of 0.50 is 0.015
+        assert_eq!(brl(50).apply_rate(rate, RoundingMode::HalfEven), Ok(brl(2)));
+        // 3% of 1.50 is 0.045
+        assert_eq!(brl(150).apply_rate(rate, RoundingMode::HalfEven), Ok(brl(4)));
+        assert_eq!(brl(150).apply_rate(rate, RoundingMode::HalfUp), Ok(brl(5)));
+        assert_eq!(brl(-150).apply_rate(rate, RoundingMode::HalfUp), Ok(brl(-5)));
+    }
+
+    #[test]
+    fn should_scale_the_rate_by_the_elapsed_part_of_the_period() {
+        let monthly = Rate::from_basis_points(200);
+        assert_eq!(brl(10000).apply_rate_pro_rata(monthly, 30, 30, RoundingMode::HalfEven), Ok(brl(200)));
+        assert_eq!(brl(10000).apply_rate_pro_rata(monthly, 45, 30, RoundingMode::HalfEven), Ok(brl(300)));
+        // 2% over 10 of 30 days is 0.6667%
+        assert_eq!(brl(10000).apply_rate_pro_rata(monthly, 10, 30, RoundingMode::HalfEven), Ok(brl(67)));
+        assert_eq!(brl(10000).apply_rate_pro_rata(monthly, 10, 30, RoundingMode::Truncate), Ok(brl(66)));
+        assert_eq!(brl(10000).apply_rate_pro_rata(monthly, 10, 0, RoundingMode::HalfEven), Err(MoneyError::DivisionByZero));
+    }
+
+    #[test]
+    fn should_scale_the_amount_by_a_share_of_it() {
+        // the fee on 30.00 out of 100.00, with 4.50 charged on the whole
+        assert_eq!(brl(450).scale(3000, 10000, RoundingMode::HalfEven), Ok(brl(135)));
+        assert_eq!(brl(100).scale(1, 3, RoundingMode::HalfEven), Ok(brl(33)));
+        assert_eq!(brl(100).scale(1, 0, RoundingMode::HalfEven), Err(MoneyError::DivisionByZero));
+    }
+
+    #[test]
+    fn should_split_without_losing_a_cent() {
+        assert_eq!(brl(10000).split(3), vec![brl(3334), brl(3333), brl(3333)]);
+        assert_eq!(brl(10001).split(3), vec![brl(3334), brl(3334), brl(3333)]);
+        assert_eq!(brl(-10000).split(3), vec![brl(-3334), brl(-3333), brl(-3333)]);
+        assert_eq!(brl(2).split(3), vec![brl(1), brl(1), brl(0)]);
+        assert_eq!(brl(100).split(0), vec![]);
+    }
+
+    #[test]
+    fn should_fail_instead_of_overflowing() {
+        assert_eq!(brl(i64::MAX).checked_add(brl(1)), Err(MoneyError::Overflow));
+        assert_eq!(brl(i64::MIN).checked_sub(brl(1)), Err(MoneyError::Overflow));
+        assert_eq!(brl(i64::MAX).checked_mul(2), Err(MoneyError::Overflow));
+        assert_eq!(Money::parse("99999999999999999999", Currency::BRL), Err(MoneyError::Overflow));
+    }
+
+    #[test]
+    fn should_display_the_amount_with_its_currency() {
+        assert_eq!(brl(2050).to_string(), "BRL 20.50");
+        assert_eq!(brl(-5).to_string(), "BRL -0.05");
+        assert_eq!(Money::from_minor(1500, Currency::JPY).to_string(), "JPY 1500");
+        assert_eq!(Money::from_minor(1500, Currency::BHD).to_string(), "BHD 1.500");
+    }
+
+    #[test]
+    fn should_respect_the_minor_unit_of_each_currency() {
+        assert_eq!(Money::parse("1500", Currency::JPY), Ok(Money::from_minor(1500, Currency::JPY)));
+        assert_eq!(Money::parse("15.5", Currency::JPY), Err(MoneyError::InvalidAmount("15.5".to_owned())));
+        assert_eq!(Money::parse("1.234", Currency::BHD), Ok(Money::from_minor(1234, Currency::BHD)));
+
+        let rate = Rate::from_basis_points(300);
+        // 3% of JPY 1250 is 37.5 yen, there is nothing smaller than a yen
+        assert_eq!(Money::from_minor(1250, Currency::JPY).apply_rate(rate, RoundingMode::HalfEven), Ok(Money::from_minor(38, Currency::JPY)));
+    }
+
+    #[test]
+    fn should_look_up_currencies_by_their_iso_code() {
+        assert_eq!("BRL".parse(), Ok(Currency::BRL));
+        assert_eq!("usd".parse(), Ok(Currency::USD));
+        assert_eq!("XXX".parse::<Currency>(), Err(MoneyError::UnknownCurrency("XXX".to_owned())));
+        assert_eq!(Currency::BRL.numeric_code(), 986);
+    }
+
+    #[test]
+    fn should_refuse_to_sum_amounts_in_different_currencies() {
+        assert_eq!(Money::sum([brl(100), brl(250)], Currency::BRL), Ok(brl(350)));
+        assert_eq!(
+            Money::sum([brl(100), Money::from_minor(250, Currency::USD)], Currency::BRL),
+            Err(MoneyError::CurrencyMismatch { expected: Currency::BRL, found: Currency::USD })
+        );
+    }
+}
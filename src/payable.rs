@@ -0,0 +1,783 @@
+use std::fmt;
+
+use chrono::{DateTime, Months, NaiveDate, Utc};
+
+use crate::calendar::SettlementSchedule;
+use crate::fees::FeeSchedule;
+use crate::merchant::Merchant;
+use crate::money::{Currency, Money, MoneyError};
+use crate::transaction::{PaymentMethod, Transaction, TransactionId, TransactionStatus};
+use crate::Unmasked;
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
+#[non_exhaustive]
+pub enum PayableStatus {
+    Paid,
+    WaitingFunds,
+    Anticipated,
+    Chargedback,
+    Refunded,
+    Cancelled,
+}
+
+impl PayableStatus {
+    /// Whether a payable may move from this status to `to`. Funds still waiting can go
+    /// anywhere, paid funds can only be clawed back, and every other status is final.
+    pub fn can_transition_to(&self, to: PayableStatus) -> bool {
+        use PayableStatus::*;
+        matches!(
+            (self, to),
+            (WaitingFunds, Paid | Anticipated | Chargedback | Refunded | Cancelled) | (Paid, Chargedback | Refunded)
+        )
+    }
+}
+
+/// A status change in the life of a payable and when it happened.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StatusChange {
+    pub from: PayableStatus,
+    pub to: PayableStatus,
+    pub at: DateTime<Utc>,
+}
+
+#[derive(PartialEq, Debug)]
+#[non_exhaustive]
+pub enum PayableError {
+    IllegalTransition { from: PayableStatus, to: PayableStatus },
+    MerchantMismatch { expected: String, found: String },
+    NotApproved,
+    NotCaptured { status: TransactionStatus },
+    OnHold,
+    InconsistentAmounts { gross: Money, fee: Money, net: Money },
+    Money(MoneyError),
+}
+
+impl fmt::Display for PayableError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PayableError::IllegalTransition { from, to } => write!(f, "a {from:?} payable cannot become {to:?}"),
+            PayableError::MerchantMismatch { expected, found } => {
+                write!(f, "transaction belongs to merchant {found:?}, not {expected:?}")
+            }
+            PayableError::NotApproved => write!(f, "only transactions the acquirer approved are paid out"),
+            PayableError::NotCaptured { status } => write!(f, "only captured transactions are paid out, this one is {status:?}"),
+            PayableError::OnHold => f.write_str("payable is on hold"),
+            PayableError::InconsistentAmounts { gross, fee, net } => {
+                write!(f, "net {net} is not gross {gross} minus fee {fee}")
+            }
+            PayableError::Money(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for PayableError {}
+
+impl From<MoneyError> for PayableError {
+    fn from(err: MoneyError) -> Self {
+        PayableError::Money(err)
+    }
+}
+
+/// Which of the installments of a transaction a payable pays, e.g. 2 of 3.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Installment {
+    number: u8,
+    count: u8,
+}
+
+impl Installment {
+    /// The whole transaction at once, for payables that are not tied to one installment.
+    pub(crate) const SINGLE: Installment = Installment { number: 1, count: 1 };
+
+    pub fn number(&self) -> u8 {
+        self.number
+    }
+
+    pub fn count(&self) -> u8 {
+        self.count
+    }
+}
+
+impl fmt::Display for Installment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "installment {} of {}", self.number, self.count)
+    }
+}
+
+#[derive(Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "PayableRecord", try_from = "PayableRecord")
+)]
+pub struct Payable {
+    status: PayableStatus,
+    tx: Transaction,
+    installment: Installment,
+    gross: Money,
+    fee: Money,
+    net: Money,
+    refunded: Money,
+    chargedback: Money,
+    due_date: NaiveDate,
+    settlement_date: Option<NaiveDate>,
+    on_hold: bool,
+    history: Vec<StatusChange>,
+}
+
+impl Payable {
+    pub(crate) fn new(status: PayableStatus, tx: Transaction, installment: Installment, gross: Money, fee: Money, due_date: NaiveDate, settlement_date: Option<NaiveDate>) -> Result<Self, MoneyError> {
+        Ok(Payable {
+            status,
+            tx,
+            installment,
+            gross,
+            fee,
+            net: gross.checked_sub(fee)?,
+            refunded: Money::zero(gross.currency()),
+            chargedback: Money::zero(gross.currency()),
+            due_date,
+            settlement_date,
+            on_hold: false,
+            history: Vec::new(),
+        })
+    }
+
+    pub fn status(&self) -> &PayableStatus {
+        &self.status
+    }
+
+    /// Every status change since the payable was created, oldest first.
+    pub fn history(&self) -> &[StatusChange] {
+        &self.history
+    }
+
+    /// Moves the payable to `to`, recording the change as made at `at`. Outside the crate
+    /// payables only change through the operations that keep the rest consistent:
+    /// settling, cancelling, anticipating, refunding and losing a dispute.
+    pub(crate) fn transition_to(&mut self, to: PayableStatus, at: DateTime<Utc>) -> Result<(), PayableError> {
+        if !self.status.can_transition_to(to) {
+            return Err(PayableError::IllegalTransition { from: self.status, to });
+        }
+        self.history.push(StatusChange { from: self.status, to, at });
+        self.status = to;
+        Ok(())
+    }
+
+    /// Pays the funds out on `on`, the merchant's local date at `at`. Funds on hold stay
+    /// where they are until the hold is released.
+    pub fn settle(&mut self, on: NaiveDate, at: DateTime<Utc>) -> Result<(), PayableError> {
+        if self.on_hold {
+            return Err(PayableError::OnHold);
+        }
+        self.transition_to(PayableStatus::Paid, at)?;
+        self.settlement_date = Some(on);
+        Ok(())
+    }
+
+    /// Cancels funds still waiting to be paid out, e.g. when the sale is called off before
+    /// settlement, recording the change as made at `at`. Funds on hold are left alone until
+    /// the hold is released.
+    pub fn cancel(&mut self, at: DateTime<Utc>) -> Result<(), PayableError> {
+        if self.on_hold {
+            return Err(PayableError::OnHold);
+        }
+        self.transition_to(PayableStatus::Cancelled, at)
+    }
+
+    pub fn transaction(&self) -> &Transaction {
+        &self.tx
+    }
+
+    /// The transaction this payable was derived from.
+    pub fn transaction_id(&self) -> TransactionId {
+        self.tx.id()
+    }
+
+    /// The part of the transaction value this payable pays, before fees.
+    pub fn gross(&self) -> Money {
+        self.gross
+    }
+
+    /// The fee charged on `gross`, fixed when the payable was created.
+    pub fn fee(&self) -> Money {
+        self.fee
+    }
+
+    /// What the merchant receives: `gross` minus `fee`.
+    pub fn net(&self) -> Money {
+        self.net
+    }
+
+    /// How much of this payable has been refunded to the cardholder so far.
+    pub fn refunded(&self) -> Money {
+        self.refunded
+    }
+
+    /// Takes a refund of `amount` out of this payable. Funds still waiting are reduced
+    /// directly, giving `fee_returned` of the fee back; paid or anticipated funds only keep
+    /// track of it, since the money is clawed back through a separate adjustment.
+    pub(crate) fn record_refund(&mut self, amount: Money, fee_returned: Money) -> Result<(), MoneyError> {
+        if self.status == PayableStatus::WaitingFunds {
+            self.gross = self.gross.checked_sub(amount)?;
+            self.fee = self.fee.checked_sub(fee_returned)?;
+            self.net = self.gross.checked_sub(self.fee)?;
+        }
+        self.refunded = self.refunded.checked_add(amount)?;
+        Ok(())
+    }
+
+    /// How much of this payable has been charged back to the cardholder through lost
+    /// disputes so far, whether or not it covered the whole payable.
+    pub fn chargedback(&self) -> Money {
+        self.chargedback
+    }
+
+    pub(crate) fn record_chargeback(&mut self, amount: Money) -> Result<(), MoneyError> {
+        self.chargedback = self.chargedback.checked_add(amount)?;
+        Ok(())
+    }
+
+    /// What the cardholder could still get back out of this payable, through a refund or a
+    /// chargeback. Refunds already came out of the gross of funds still waiting, but not out
+    /// of paid or anticipated ones.
+    pub(crate) fn outstanding(&self) -> Result<Money, MoneyError> {
+        match self.status {
+            PayableStatus::WaitingFunds => self.gross.checked_sub(self.chargedback),
+            PayableStatus::Paid | PayableStatus::Anticipated => self.gross.checked_sub(self.refunded)?.checked_sub(self.chargedback),
+            _ => Ok(Money::zero(self.gross.currency())),
+        }
+    }
+
+    pub fn installment(&self) -> Installment {
+        self.installment
+    }
+
+    /// The day the merchant is due to receive the funds.
+    pub fn due_date(&self) -> NaiveDate {
+        self.due_date
+    }
+
+    /// The day the funds were actually paid out, if they have been.
+    pub fn settlement_date(&self) -> Option<NaiveDate> {
+        self.settlement_date
+    }
+
+    /// Whether the funds are held back, e.g. while a dispute over the transaction is open.
+    /// Payables on hold are neither settled nor anticipated.
+    pub fn is_on_hold(&self) -> bool {
+        self.on_hold
+    }
+
+    pub(crate) fn set_on_hold(&mut self, on_hold: bool) {
+        self.on_hold = on_hold;
+    }
+
+    pub fn currency(&self) -> Currency {
+        self.tx.currency()
+    }
+
+    /// Adds up the fees of `payables`, which must all be in `currency`.
+    pub fn total_fees<'a, I>(payables: I, currency: Currency) -> Result<Money, MoneyError>
+    where
+        I: IntoIterator<Item = &'a Payable>,
+    {
+        payables.into_iter().try_fold(Money::zero(currency), |total, payable| {
+            total.checked_add(payable.fee)
+        })
+    }
+
+    pub fn unmasked(&self) -> Unmasked<'_, Payable> {
+        Unmasked(self)
+    }
+}
+
+impl fmt::Debug for Payable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Payable")
+            .field("status", &self.status)
+            .field("tx", &self.tx)
+            .field("installment", &self.installment)
+            .field("gross", &self.gross)
+            .field("fee", &self.fee)
+            .field("net", &self.net)
+            .field("refunded", &self.refunded)
+            .field("chargedback", &self.chargedback)
+            .field("due_date", &self.due_date)
+            .field("settlement_date", &self.settlement_date)
+            .field("on_hold", &self.on_hold)
+            .field("history", &self.history)
+            .finish()
+    }
+}
+
+impl fmt::Display for Payable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} payable of {} ({} of {}) due on {}", self.status, self.gross, self.installment, self.tx, self.due_date)
+    }
+}
+
+impl fmt::Debug for Unmasked<'_, Payable> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Payable")
+            .field("status", &self.0.status)
+            .field("tx", &self.0.tx.unmasked())
+            .field("installment", &self.0.installment)
+            .field("gross", &self.0.gross)
+            .field("fee", &self.0.fee)
+            .field("net", &self.0.net)
+            .field("refunded", &self.0.refunded)
+            .field("chargedback", &self.0.chargedback)
+            .field("due_date", &self.0.due_date)
+            .field("settlement_date", &self.0.settlement_date)
+            .field("on_hold", &self.0.on_hold)
+            .field("history", &self.0.history)
+            .finish()
+    }
+}
+
+/// What a payable is serialized as. Deserializing checks that the amounts add up in the
+/// currency of the transaction and that the status was reached through legal changes, so
+/// a payload cannot do what `transition_to` being crate-private prevents.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct PayableRecord {
+    status: PayableStatus,
+    transaction: Transaction,
+    installment: Installment,
+    gross: Money,
+    fee: Money,
+    net: Money,
+    refunded: Money,
+    chargedback: Money,
+    due_date: NaiveDate,
+    settlement_date: Option<NaiveDate>,
+    on_hold: bool,
+    history: Vec<StatusChange>,
+}
+
+#[cfg(feature = "serde")]
+impl From<Payable> for PayableRecord {
+    fn from(payable: Payable) -> Self {
+        PayableRecord {
+            status: payable.status,
+            transaction: payable.tx,
+            installment: payable.installment,
+            gross: payable.gross,
+            fee: payable.fee,
+            net: payable.net,
+            refunded: payable.refunded,
+            chargedback: payable.chargedback,
+            due_date: payable.due_date,
+            settlement_date: payable.settlement_date,
+            on_hold: payable.on_hold,
+            history: payable.history,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<PayableRecord> for Payable {
+    type Error = PayableError;
+
+    fn try_from(record: PayableRecord) -> Result<Self, Self::Error> {
+        if record.transaction.status() != TransactionStatus::Captured {
+            return Err(PayableError::NotCaptured { status: record.transaction.status() });
+        }
+        let currency = record.transaction.currency();
+        for amount in [record.gross, record.fee, record.net, record.refunded, record.chargedback] {
+            if amount.currency() != currency {
+                return Err(MoneyError::CurrencyMismatch { expected: currency, found: amount.currency() }.into());
+            }
+        }
+        if record.gross.checked_sub(record.fee)? != record.net {
+            return Err(PayableError::InconsistentAmounts { gross: record.gross, fee: record.fee, net: record.net });
+        }
+        // payables start out waiting for funds or already paid, and only move on legally
+        let start = record.history.first().map_or(record.status, |change| change.from);
+        if !matches!(start, PayableStatus::WaitingFunds | PayableStatus::Paid) {
+            return Err(PayableError::IllegalTransition { from: start, to: start });
+        }
+        let reached = record.history.iter().try_fold(start, |from, change| {
+            if change.from != from || !from.can_transition_to(change.to) {
+                return Err(PayableError::IllegalTransition { from, to: change.to });
+            }
+            Ok(change.to)
+        })?;
+        if reached != record.status {
+            return Err(PayableError::IllegalTransition { from: reached, to: record.status });
+        }
+        if record.on_hold && record.status != PayableStatus::WaitingFunds {
+            return Err(PayableError::OnHold);
+        }
+        Ok(Payable {
+            status: record.status,
+            tx: record.transaction,
+            installment: record.installment,
+            gross: record.gross,
+            fee: record.fee,
+            net: record.net,
+            refunded: record.refunded,
+            chargedback: record.chargedback,
+            due_date: record.due_date,
+            settlement_date: record.settlement_date,
+            on_hold: record.on_hold,
+            history: record.history,
+        })
+    }
+}
+
+impl Payable {
+    pub fn from_transaction(tx: Transaction, merchant: &Merchant) -> Result<Vec<Self>, PayableError> {
+        Payable::from_transaction_with(tx, merchant, &SettlementSchedule::default(), &FeeSchedule::default())
+    }
+
+    /// Turns the captured amount of `tx` into one payable per installment, dating them by the
+    /// day the transaction happened in the merchant's timezone rather than the server's.
+    /// Credit installments fall due a month apart, each according to `settlement`. The fee
+    /// `fees` charges on the whole captured amount is split across them like the amount is.
+    /// `merchant` has to be the one the transaction was made for, and the acquirer has to
+    /// have approved it.
+    pub fn from_transaction_with(tx: Transaction, merchant: &Merchant, settlement: &SettlementSchedule, fees: &FeeSchedule) -> Result<Vec<Self>, PayableError> {
+        if tx.merchant_id() != merchant.id() {
+            return Err(PayableError::MerchantMismatch { expected: merchant.id().to_owned(), found: tx.merchant_id().to_owned() });
+        }
+        if !tx.is_approved() {
+            return Err(PayableError::NotApproved);
+        }
+        if tx.status() != TransactionStatus::Captured {
+            return Err(PayableError::NotCaptured { status: tx.status() });
+        }
+        let now = merchant.local_date(tx.created_at());
+        let count = tx.installments();
+        let fee = fees.policy_for(tx.method()).fee_for(tx.captured())?;
+        tx.captured()
+            .split(count.into())
+            .into_iter()
+            .zip(fee.split(count.into()))
+            .zip(1..=count)
+            .map(|((gross, fee), number)| {
+                let installment = Installment { number, count };
+                match tx.method() {
+                    PaymentMethod::Credit => {
+                        let month = now + Months::new(u32::from(number) - 1);
+                        Payable::new(PayableStatus::WaitingFunds, tx.clone(), installment, gross, fee, settlement.due_date(month), None)
+                    }
+                    PaymentMethod::Debit => {
+                        Payable::new(PayableStatus::Paid, tx.clone(), installment, gross, fee, now, Some(now))
+                    }
+                }
+            })
+            .collect::<Result<_, MoneyError>>()
+            .map_err(PayableError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acquirer::{AuthorizationResult, DeclineReason};
+    use crate::calendar::{BusinessCalendar, SettlementRule};
+    use crate::clock::FixedClock;
+    use crate::fees::{self, FlatFee, PercentageFee};
+    use crate::money::{Rate, RoundingMode};
+    use crate::test_support::{approval, brl, card, merchant, merchant_named};
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_make_payable_with_debit() {
+        let card = card();
+        let clock = FixedClock::new(Utc.with_ymd_and_hms(2024, 3, 15, 12, 0, 0).unwrap());
+        let tx = Transaction::try_new_at(&merchant(), brl(10000), "Test Transaction".to_owned(), PaymentMethod::Debit, card, approval(), &clock).unwrap();
+
+        let payable = Payable::from_transaction(tx, &merchant()).unwrap().remove(0);
+        let today = NaiveDate::from_ymd_opt(2024, 3, 15).unwrap();
+
+        assert_eq!(payable.status, PayableStatus::Paid);
+        assert_eq!(payable.gross(), brl(10000));
+        assert_eq!(payable.fee(), brl(300));
+        assert_eq!(payable.net(), brl(9700));
+        assert_eq!(payable.due_date(), today);
+        assert_eq!(payable.settlement_date(), Some(today));
+    }
+
+    #[test]
+    fn should_carry_the_transaction_id_onto_every_installment() {
+        let tx = Transaction::try_new(&merchant(), brl(10000), "Test Transaction".to_owned(), PaymentMethod::Credit, card(), approval())
+            .unwrap()
+            .with_installments(3)
+            .unwrap();
+        let id = tx.id();
+
+        let payables = Payable::from_transaction(tx, &merchant()).unwrap();
+
+        assert!(payables.iter().all(|payable| payable.transaction_id() == id));
+    }
+
+    #[test]
+    fn should_refuse_to_pay_a_transaction_to_another_merchant() {
+        let tx = Transaction::try_new(&merchant(), brl(10000), "Test Transaction".to_owned(), PaymentMethod::Debit, card(), approval()).unwrap();
+        let other = merchant_named("merchant-2");
+
+        assert_eq!(
+            Payable::from_transaction(tx, &other).unwrap_err(),
+            PayableError::MerchantMismatch { expected: "merchant-2".to_owned(), found: "merchant-1".to_owned() }
+        );
+    }
+
+    #[test]
+    fn should_refuse_to_pay_out_a_transaction_the_acquirer_did_not_approve() {
+        let tx = Transaction::authorize(&merchant(), brl(10000), "Test Transaction".to_owned(), PaymentMethod::Debit, card()).unwrap();
+        let decline = AuthorizationResult::declined(DeclineReason::SuspectedFraud, "000000001", "59").unwrap();
+        let declined = Transaction::try_new(&merchant(), brl(10000), "Test Transaction".to_owned(), PaymentMethod::Debit, card(), decline).unwrap();
+
+        assert_eq!(Payable::from_transaction(tx, &merchant()).unwrap_err(), PayableError::NotApproved);
+        assert_eq!(Payable::from_transaction(declined, &merchant()).unwrap_err(), PayableError::NotApproved);
+    }
+
+    #[test]
+    fn should_pay_out_only_the_captured_amount() {
+        let clock = FixedClock::new(Utc.with_ymd_and_hms(2024, 3, 15, 12, 0, 0).unwrap());
+        let mut tx = Transaction::authorize_at(&merchant(), brl(10000), "Test Transaction".to_owned(), PaymentMethod::Debit, card(), &clock)
+            .unwrap()
+            .with_authorization(approval())
+            .unwrap();
+        assert_eq!(
+            Payable::from_transaction(tx.clone(), &merchant()).unwrap_err(),
+            PayableError::NotCaptured { status: TransactionStatus::Authorized }
+        );
+
+        tx.capture(brl(6000), &clock).unwrap();
+        let payable = Payable::from_transaction(tx, &merchant()).unwrap().remove(0);
+
+        assert_eq!((payable.gross(), payable.fee()), (brl(6000), brl(180)));
+    }
+
+    #[test]
+    fn test_make_payable_with_credit() {
+        let card = card();
+        let clock = FixedClock::new(Utc.with_ymd_and_hms(2024, 3, 15, 12, 0, 0).unwrap());
+        let tx = Transaction::try_new_at(&merchant(), brl(10000), "Test Transaction".to_owned(), PaymentMethod::Credit, card, approval(), &clock).unwrap();
+
+        let payable = Payable::from_transaction(tx, &merchant()).unwrap().remove(0);
+        // 30 days later is Sunday 2024-04-14, when no money moves
+        let next_business_day = NaiveDate::from_ymd_opt(2024, 4, 15).unwrap();
+
+        assert_eq!(payable.status, PayableStatus::WaitingFunds);
+        assert_eq!(payable.fee(), brl(500));
+        assert_eq!(payable.net(), brl(9500));
+        assert_eq!(payable.due_date(), next_business_day);
+        assert_eq!(payable.settlement_date(), None);
+    }
+
+    #[test]
+    fn should_round_the_fee_to_the_cent_without_drifting() {
+        let card = card();
+        let tx = Transaction::try_new(&merchant(), brl(2050), "Test Transaction".to_owned(), PaymentMethod::Debit, card, approval()).unwrap();
+
+        let truncating = FeeSchedule::new(
+            PercentageFee::new(fees::DEFAULT_FEE_FOR_DEBIT).with_rounding(RoundingMode::Truncate),
+            PercentageFee::new(fees::DEFAULT_FEE_FOR_CREDIT).with_rounding(RoundingMode::Truncate),
+        );
+
+        let payable = Payable::from_transaction(tx.clone(), &merchant()).unwrap().remove(0);
+        let truncated = Payable::from_transaction_with(tx, &merchant(), &SettlementSchedule::default(), &truncating).unwrap().remove(0);
+
+        assert_eq!(payable.fee(), brl(62));
+        assert_eq!(truncated.fee(), brl(61));
+    }
+
+    #[test]
+    fn should_keep_the_currency_of_the_transaction_on_the_payable() {
+        let card = card();
+        let tx = Transaction::try_new(&merchant(), Money::from_minor(1250, Currency::JPY), "Test Transaction".to_owned(), PaymentMethod::Debit, card, approval()).unwrap();
+
+        let payable = Payable::from_transaction(tx, &merchant()).unwrap().remove(0);
+
+        assert_eq!(payable.currency(), Currency::JPY);
+        assert_eq!(payable.fee(), Money::from_minor(38, Currency::JPY));
+    }
+
+    #[test]
+    fn should_refuse_to_total_fees_across_currencies() {
+        let card = card();
+        let in_reais = Payable::from_transaction(Transaction::try_new(&merchant(), brl(10000), "Test Transaction".to_owned(), PaymentMethod::Debit, card.clone(), approval()).unwrap(), &merchant()).unwrap().remove(0);
+        let in_dollars = Payable::from_transaction(Transaction::try_new(&merchant(), Money::from_minor(10000, Currency::USD), "Test Transaction".to_owned(), PaymentMethod::Debit, card, approval()).unwrap(), &merchant()).unwrap().remove(0);
+
+        assert_eq!(Payable::total_fees([&in_reais], Currency::BRL), Ok(brl(300)));
+        assert_eq!(
+            Payable::total_fees([&in_reais, &in_dollars], Currency::BRL),
+            Err(MoneyError::CurrencyMismatch { expected: Currency::BRL, found: Currency::USD })
+        );
+    }
+
+    #[test]
+    fn should_calculate_the_fee_against_a_negotiated_schedule() {
+        let card = card();
+        let tx = Transaction::try_new(&merchant(), brl(10000), "Test Transaction".to_owned(), PaymentMethod::Credit, card, approval()).unwrap();
+        let negotiated = FeeSchedule::new(FlatFee::new(brl(50)), PercentageFee::new(Rate::from_basis_points(250)));
+
+        let payable = Payable::from_transaction_with(tx, &merchant(), &SettlementSchedule::default(), &negotiated).unwrap().remove(0);
+
+        assert_eq!(payable.fee(), brl(250));
+        assert_eq!(payable.net(), brl(9750));
+    }
+
+    #[test]
+    fn should_mask_sensitive_card_data_when_formatting() {
+        let tx = Transaction::try_new(&merchant(), brl(2050), "Test Transaction".to_owned(), PaymentMethod::Debit, card(), approval()).unwrap();
+        let payable = Payable::from_transaction(tx, &merchant()).unwrap().remove(0);
+
+        assert_eq!(card().to_string(), "Visa **** **** **** 1111 (R. D.)");
+        for formatted in [format!("{:?}", card()), format!("{:?}", payable.tx), format!("{payable:?}"), payable.to_string()] {
+            assert!(formatted.contains("**** **** **** 1111"), "{formatted}");
+            assert!(!formatted.contains("Rafael"), "{formatted}");
+            assert!(!formatted.contains("12/30"), "{formatted}");
+        }
+    }
+
+    #[test]
+    fn should_show_sensitive_card_data_only_when_explicitly_unmasked() {
+        let tx = Transaction::try_new(&merchant(), brl(2050), "Test Transaction".to_owned(), PaymentMethod::Debit, card(), approval()).unwrap();
+        let payable = Payable::from_transaction(tx, &merchant()).unwrap().remove(0);
+
+        let unmasked = format!("{:?}", payable.unmasked());
+
+        assert!(unmasked.contains("Rafael Dias"), "{unmasked}");
+        assert!(unmasked.contains("12/30"), "{unmasked}");
+        assert!(unmasked.contains("41111111"), "{unmasked}");
+    }
+
+    #[test]
+    fn should_date_the_payable_in_the_merchant_timezone() {
+        // 23:30 in São Paulo, already the next day in UTC
+        let clock = FixedClock::new(Utc.with_ymd_and_hms(2024, 3, 16, 2, 30, 0).unwrap());
+        let tx = Transaction::try_new_at(&merchant(), brl(10000), "Test Transaction".to_owned(), PaymentMethod::Debit, card(), approval(), &clock).unwrap();
+
+        assert_eq!(tx.created_at(), Utc.with_ymd_and_hms(2024, 3, 16, 2, 30, 0).unwrap());
+        let payable = Payable::from_transaction(tx, &merchant()).unwrap().remove(0);
+
+        assert_eq!(payable.due_date(), NaiveDate::from_ymd_opt(2024, 3, 15).unwrap());
+    }
+
+    #[test]
+    fn should_set_credit_payables_due_according_to_the_settlement_schedule() {
+        let clock = FixedClock::new(Utc.with_ymd_and_hms(2024, 3, 28, 12, 0, 0).unwrap());
+        let tx = Transaction::try_new_at(&merchant(), brl(10000), "Test Transaction".to_owned(), PaymentMethod::Credit, card(), approval(), &clock).unwrap();
+        let calendar = BusinessCalendar::default().with_holiday(NaiveDate::from_ymd_opt(2024, 3, 29).unwrap());
+        let schedule = SettlementSchedule::new(SettlementRule::BusinessDays(1), calendar);
+
+        let payable = Payable::from_transaction_with(tx, &merchant(), &schedule, &FeeSchedule::default()).unwrap().remove(0);
+
+        assert_eq!(payable.due_date(), NaiveDate::from_ymd_opt(2024, 4, 1).unwrap());
+    }
+
+    #[test]
+    fn should_split_an_installment_transaction_into_monthly_payables() {
+        let clock = FixedClock::new(Utc.with_ymd_and_hms(2024, 3, 15, 12, 0, 0).unwrap());
+        let tx = Transaction::try_new_at(&merchant(), brl(10000), "Test Transaction".to_owned(), PaymentMethod::Credit, card(), approval(), &clock)
+            .unwrap()
+            .with_installments(3)
+            .unwrap();
+
+        let payables = Payable::from_transaction(tx, &merchant()).unwrap();
+
+        let amounts: Vec<_> = payables.iter().map(Payable::gross).collect();
+        assert_eq!(amounts, vec![brl(3334), brl(3333), brl(3333)]);
+        let installments: Vec<_> = payables.iter().map(|payable| payable.installment().to_string()).collect();
+        assert_eq!(installments, vec!["installment 1 of 3", "installment 2 of 3", "installment 3 of 3"]);
+        let due_dates: Vec<_> = payables.iter().map(Payable::due_date).collect();
+        assert_eq!(
+            due_dates,
+            // 30 days after each monthly date, the first one rolled forward from a Sunday
+            vec![
+                NaiveDate::from_ymd_opt(2024, 4, 15).unwrap(),
+                NaiveDate::from_ymd_opt(2024, 5, 15).unwrap(),
+                NaiveDate::from_ymd_opt(2024, 6, 14).unwrap(),
+            ]
+        );
+        let fees: Vec<_> = payables.iter().map(Payable::fee).collect();
+        assert_eq!(fees, vec![brl(167), brl(167), brl(166)]);
+        assert_eq!(Payable::total_fees(&payables, Currency::BRL), Ok(brl(500)));
+    }
+
+    #[test]
+    fn should_record_every_legal_status_change() {
+        let clock = FixedClock::new(Utc.with_ymd_and_hms(2024, 3, 15, 12, 0, 0).unwrap());
+        let tx = Transaction::try_new_at(&merchant(), brl(10000), "Test Transaction".to_owned(), PaymentMethod::Credit, card(), approval(), &clock).unwrap();
+        let mut payable = Payable::from_transaction(tx, &merchant()).unwrap().remove(0);
+        let paid_at = Utc.with_ymd_and_hms(2024, 4, 15, 9, 0, 0).unwrap();
+        let chargedback_at = Utc.with_ymd_and_hms(2024, 5, 2, 9, 0, 0).unwrap();
+
+        payable.transition_to(PayableStatus::Paid, paid_at).unwrap();
+        payable.transition_to(PayableStatus::Chargedback, chargedback_at).unwrap();
+
+        assert_eq!(payable.status, PayableStatus::Chargedback);
+        assert_eq!(
+            payable.history(),
+            [
+                StatusChange { from: PayableStatus::WaitingFunds, to: PayableStatus::Paid, at: paid_at },
+                StatusChange { from: PayableStatus::Paid, to: PayableStatus::Chargedback, at: chargedback_at },
+            ]
+        );
+    }
+
+    #[test]
+    fn should_refuse_illegal_status_changes() {
+        let tx = Transaction::try_new(&merchant(), brl(10000), "Test Transaction".to_owned(), PaymentMethod::Debit, card(), approval()).unwrap();
+        let mut payable = Payable::from_transaction(tx, &merchant()).unwrap().remove(0);
+        let now = Utc.with_ymd_and_hms(2024, 3, 15, 12, 0, 0).unwrap();
+
+        assert_eq!(
+            payable.transition_to(PayableStatus::WaitingFunds, now),
+            Err(PayableError::IllegalTransition { from: PayableStatus::Paid, to: PayableStatus::WaitingFunds })
+        );
+        assert_eq!(
+            payable.transition_to(PayableStatus::Cancelled, now),
+            Err(PayableError::IllegalTransition { from: PayableStatus::Paid, to: PayableStatus::Cancelled })
+        );
+        payable.transition_to(PayableStatus::Refunded, now).unwrap();
+        assert_eq!(
+            payable.transition_to(PayableStatus::Chargedback, now),
+            Err(PayableError::IllegalTransition { from: PayableStatus::Refunded, to: PayableStatus::Chargedback })
+        );
+        assert_eq!(payable.history().len(), 1);
+    }
+
+    #[test]
+    fn should_not_settle_a_payable_on_hold() {
+        let tx = Transaction::try_new(&merchant(), brl(10000), "Test Transaction".to_owned(), PaymentMethod::Credit, card(), approval()).unwrap();
+        let mut payable = Payable::from_transaction(tx, &merchant()).unwrap().remove(0);
+        let due = payable.due_date();
+        let now = Utc.with_ymd_and_hms(2024, 4, 15, 12, 0, 0).unwrap();
+        payable.set_on_hold(true);
+
+        assert_eq!(payable.settle(due, now), Err(PayableError::OnHold));
+        payable.set_on_hold(false);
+        payable.settle(due, now).unwrap();
+        assert_eq!((payable.status, payable.settlement_date()), (PayableStatus::Paid, Some(due)));
+    }
+
+    #[test]
+    fn should_cancel_only_funds_still_waiting_and_not_on_hold() {
+        let credit = Transaction::try_new(&merchant(), brl(10000), "Test Transaction".to_owned(), PaymentMethod::Credit, card(), approval()).unwrap();
+        let debit = Transaction::try_new(&merchant(), brl(10000), "Test Transaction".to_owned(), PaymentMethod::Debit, card(), approval()).unwrap();
+        let mut waiting = Payable::from_transaction(credit, &merchant()).unwrap().remove(0);
+        let mut paid = Payable::from_transaction(debit, &merchant()).unwrap().remove(0);
+        let now = Utc.with_ymd_and_hms(2024, 3, 20, 12, 0, 0).unwrap();
+
+        waiting.set_on_hold(true);
+        assert_eq!(waiting.cancel(now), Err(PayableError::OnHold));
+        waiting.set_on_hold(false);
+        waiting.cancel(now).unwrap();
+
+        assert_eq!(waiting.history(), [StatusChange { from: PayableStatus::WaitingFunds, to: PayableStatus::Cancelled, at: now }]);
+        assert_eq!(
+            paid.cancel(now),
+            Err(PayableError::IllegalTransition { from: PayableStatus::Paid, to: PayableStatus::Cancelled })
+        );
+    }
+}
@@ -0,0 +1,271 @@
+use std::fmt;
+
+use crate::acquirer::{Acquirer, AcquirerError, AuthorizationResult, DeclineReason};
+use crate::card::SensitiveAuthData;
+use crate::clock::Clock;
+use crate::merchant::Merchant;
+use crate::money::Money;
+use crate::payable::Payable;
+use crate::refund::{Refund, RefundError, Refunder};
+use crate::transaction::{Transaction, TransactionError};
+
+#[derive(PartialEq, Debug)]
+#[non_exhaustive]
+pub enum ProcessingError {
+    Declined(DeclineReason),
+    Acquirer(AcquirerError),
+    Transaction(TransactionError),
+    Refund(RefundError),
+}
+
+impl fmt::Display for ProcessingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProcessingError::Declined(reason) => write!(f, "declined: {reason}"),
+            ProcessingError::Acquirer(err) => err.fmt(f),
+            ProcessingError::Transaction(err) => err.fmt(f),
+            ProcessingError::Refund(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for ProcessingError {}
+
+impl From<AcquirerError> for ProcessingError {
+    fn from(err: AcquirerError) -> Self {
+        ProcessingError::Acquirer(err)
+    }
+}
+
+impl From<TransactionError> for ProcessingError {
+    fn from(err: TransactionError) -> Self {
+        ProcessingError::Transaction(err)
+    }
+}
+
+impl From<RefundError> for ProcessingError {
+    fn from(err: RefundError) -> Self {
+        ProcessingError::Refund(err)
+    }
+}
+
+/// Runs transactions through an acquirer. Authorizations, captures, voids and refunds are
+/// checked locally first, so the acquirer is never asked for something the transaction
+/// cannot take, and only applied once it approves them, so a decline leaves everything
+/// unchanged.
+#[derive(Debug)]
+pub struct Processor<A> {
+    acquirer: A,
+}
+
+impl<A: Acquirer> Processor<A> {
+    pub fn new(acquirer: A) -> Self {
+        Processor { acquirer }
+    }
+
+    pub fn acquirer(&self) -> &A {
+        &self.acquirer
+    }
+
+    /// Asks the issuer to authorize `tx`, as built by `Transaction::authorize`, and records
+    /// the answer on it: a declined transaction becomes `Declined` rather than an error, so
+    /// the reason stays with it. If the acquirer cannot be reached `tx` is left as it was,
+    /// ready to be tried again. `auth` is dropped, and so wiped, as soon as the acquirer has
+    /// answered.
+    pub fn authorize(&mut self, tx: &mut Transaction, auth: SensitiveAuthData) -> Result<(), ProcessingError> {
+        tx.check_authorizable()?;
+        let result = self.acquirer.authorize(tx, &auth)?;
+        drop(auth);
+        *tx = tx.clone().with_authorization(result)?;
+        Ok(())
+    }
+
+    /// Authorizes `tx` and, if approved, captures its whole value straight away. When the
+    /// capture fails `tx` is still an approved authorization, which can be captured again or
+    /// voided to release the funds.
+    pub fn sale(&mut self, tx: &mut Transaction, auth: SensitiveAuthData, clock: &impl Clock) -> Result<(), ProcessingError> {
+        self.authorize(tx, auth)?;
+        if tx.is_approved() {
+            let value = tx.value();
+            self.capture(tx, value, clock)?;
+        }
+        Ok(())
+    }
+
+    pub fn capture(&mut self, tx: &mut Transaction, amount: Money, clock: &impl Clock) -> Result<(), ProcessingError> {
+        let mut captured = tx.clone();
+        if let Err(err) = captured.capture(amount, clock) {
+            // an authorization that ran out of time has expired whatever the acquirer says
+            *tx = captured;
+            return Err(err.into());
+        }
+        approved(self.acquirer.capture(tx, amount)?)?;
+        *tx = captured;
+        Ok(())
+    }
+
+    pub fn void(&mut self, tx: &mut Transaction) -> Result<(), ProcessingError> {
+        let mut voided = tx.clone();
+        voided.void()?;
+        approved(self.acquirer.void(tx)?)?;
+        *tx = voided;
+        Ok(())
+    }
+
+    /// Refunds `amount` of the transaction behind `payables` as `refunder` would, once the
+    /// acquirer has given it back to the cardholder.
+    pub fn refund(
+        &mut self,
+        payables: &mut [Payable],
+        amount: Money,
+        refunder: &Refunder,
+        merchant: &Merchant,
+        clock: &impl Clock,
+    ) -> Result<Refund, ProcessingError> {
+        let mut refunded = payables.to_vec();
+        let refund = refunder.refund(&mut refunded, amount, merchant, clock)?;
+        approved(self.acquirer.refund(refunded[0].transaction(), amount)?)?;
+        payables.clone_from_slice(&refunded);
+        Ok(refund)
+    }
+}
+
+fn approved(result: AuthorizationResult) -> Result<(), ProcessingError> {
+    match result.decline_reason() {
+        None => Ok(()),
+        Some(reason) => Err(ProcessingError::Declined(reason)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acquirer::{DeclineKind, SimulatedAcquirer};
+    use crate::clock::FixedClock;
+    use crate::payable::PayableError;
+    use crate::refund::FeeRefund;
+    use crate::test_support::{brl, card_numbered, clock_on, merchant};
+    use crate::transaction::{PaymentMethod, TransactionStatus};
+
+    fn clock() -> FixedClock {
+        clock_on(2024, 3, 15)
+    }
+
+    fn authorization(number: &str, value: Money) -> (Transaction, SensitiveAuthData) {
+        let card = card_numbered(number);
+        let auth = SensitiveAuthData::try_new("123".to_owned(), &card).unwrap();
+        let tx = Transaction::authorize_at(&merchant(), value, "Test Transaction".to_owned(), PaymentMethod::Credit, card, &clock()).unwrap();
+        (tx, auth)
+    }
+
+    #[test]
+    fn should_record_the_authorization_result_on_the_transaction() {
+        let mut processor = Processor::new(SimulatedAcquirer::new());
+
+        let (mut declined, auth) = authorization("4000000000009995", brl(10000));
+        processor.sale(&mut declined, auth, &clock()).unwrap();
+        let result = declined.authorization().unwrap();
+        assert_eq!((declined.status(), declined.captured()), (TransactionStatus::Declined, brl(0)));
+        assert_eq!((result.decline_reason(), result.decline_kind()), (Some(DeclineReason::InsufficientFunds), Some(DeclineKind::Soft)));
+        assert_eq!(result.response_code(), "51");
+        assert_eq!(Payable::from_transaction(declined, &merchant()).unwrap_err(), PayableError::NotApproved);
+
+        let (mut tx, auth) = authorization("4111111111111111", brl(10000));
+        processor.sale(&mut tx, auth, &clock()).unwrap();
+        assert_eq!((tx.status(), tx.captured()), (TransactionStatus::Captured, brl(10000)));
+        assert_eq!(tx.authorization().and_then(AuthorizationResult::approval_code), Some("000001"));
+        assert_eq!(tx.authorization().map(AuthorizationResult::nsu), Some("000000002"));
+    }
+
+    /// Approves like the simulator but times out on every capture.
+    struct CaptureTimesOut(SimulatedAcquirer);
+
+    impl Acquirer for CaptureTimesOut {
+        fn authorize(&mut self, tx: &Transaction, auth: &SensitiveAuthData) -> Result<AuthorizationResult, AcquirerError> {
+            self.0.authorize(tx, auth)
+        }
+
+        fn capture(&mut self, _tx: &Transaction, _amount: Money) -> Result<AuthorizationResult, AcquirerError> {
+            Err(AcquirerError::Unavailable("timed out".to_owned()))
+        }
+
+        fn void(&mut self, tx: &Transaction) -> Result<AuthorizationResult, AcquirerError> {
+            self.0.void(tx)
+        }
+
+        fn refund(&mut self, tx: &Transaction, amount: Money) -> Result<AuthorizationResult, AcquirerError> {
+            self.0.refund(tx, amount)
+        }
+    }
+
+    #[test]
+    fn should_keep_the_authorization_when_the_capture_of_a_sale_fails() {
+        let mut processor = Processor::new(CaptureTimesOut(SimulatedAcquirer::new()));
+        let (mut tx, auth) = authorization("4111111111111111", brl(10000));
+
+        let result = processor.sale(&mut tx, auth, &clock());
+
+        assert!(matches!(result, Err(ProcessingError::Acquirer(AcquirerError::Unavailable(_)))));
+        assert!(tx.is_approved());
+        assert_eq!(tx.status(), TransactionStatus::Authorized);
+        processor.void(&mut tx).unwrap();
+        assert_eq!(tx.status(), TransactionStatus::Voided);
+    }
+
+    #[test]
+    fn should_capture_and_void_through_the_acquirer() {
+        let mut processor = Processor::new(SimulatedAcquirer::new());
+        let (mut captured, auth) = authorization("4111111111111111", brl(10000));
+        processor.authorize(&mut captured, auth).unwrap();
+        let (mut voided, auth) = authorization("4111111111111111", brl(10000));
+        processor.authorize(&mut voided, auth).unwrap();
+
+        processor.capture(&mut captured, brl(7000), &clock()).unwrap();
+        processor.void(&mut voided).unwrap();
+
+        assert_eq!(captured.captured(), brl(7000));
+        assert_eq!(voided.status(), TransactionStatus::Voided);
+        assert!(matches!(processor.void(&mut captured), Err(ProcessingError::Transaction(_))));
+    }
+
+    #[test]
+    fn should_not_ask_the_acquirer_to_authorize_a_transaction_twice() {
+        let mut processor = Processor::new(SimulatedAcquirer::new());
+        let (mut tx, auth) = authorization("4111111111111111", brl(10000));
+        processor.authorize(&mut tx, auth).unwrap();
+        let (mut voided, auth) = authorization("4111111111111111", brl(10000));
+        voided.void().unwrap();
+
+        let again = SensitiveAuthData::try_new("123".to_owned(), tx.card()).unwrap();
+        let result = processor.authorize(&mut tx, again);
+        assert_eq!(result, Err(ProcessingError::Transaction(TransactionError::AlreadyAuthorized)));
+        assert_eq!(
+            processor.authorize(&mut voided, auth),
+            Err(ProcessingError::Transaction(TransactionError::IllegalTransition {
+                from: TransactionStatus::Voided,
+                to: TransactionStatus::Authorized
+            }))
+        );
+
+        let (mut next, auth) = authorization("4111111111111111", brl(10000));
+        processor.authorize(&mut next, auth).unwrap();
+        assert_eq!(next.authorization().map(AuthorizationResult::nsu), Some("000000002"));
+    }
+
+    #[test]
+    fn should_leave_the_payables_alone_unless_the_acquirer_refunds() {
+        let mut processor = Processor::new(SimulatedAcquirer::new());
+        let refunder = Refunder::new(FeeRefund::Proportional);
+        let (mut tx, auth) = authorization("4111111111111111", brl(10000));
+        processor.sale(&mut tx, auth, &clock()).unwrap();
+        let mut payables = Payable::from_transaction(tx, &merchant()).unwrap();
+
+        let mut elsewhere = Processor::new(SimulatedAcquirer::new());
+        let result = elsewhere.refund(&mut payables, brl(4000), &refunder, &merchant(), &clock());
+        assert!(matches!(result, Err(ProcessingError::Acquirer(AcquirerError::UnknownTransaction(_)))));
+        assert_eq!(payables[0].gross(), brl(10000));
+
+        processor.refund(&mut payables, brl(4000), &refunder, &merchant(), &clock()).unwrap();
+        assert_eq!(payables[0].gross(), brl(6000));
+    }
+}
@@ -0,0 +1,315 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use crate::clock::Clock;
+use crate::merchant::Merchant;
+use crate::money::{Money, MoneyError, RoundingMode};
+use crate::payable::{Payable, PayableStatus};
+
+/// Who bears the fee on the refunded part of a transaction.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
+#[non_exhaustive]
+pub enum FeeRefund {
+    /// The merchant gets back the share of the fee charged on the refunded amount.
+    #[default]
+    Proportional,
+    /// The PSP keeps the whole fee, so the merchant still pays it on refunded amounts.
+    Retained,
+}
+
+#[derive(PartialEq, Debug)]
+#[non_exhaustive]
+pub enum RefundError {
+    NothingToRefund,
+    InvalidAmount(Money),
+    MixedTransactions,
+    MerchantMismatch { expected: String, found: String },
+    ExceedsRemaining { requested: Money, remaining: Money },
+    Money(MoneyError),
+}
+
+impl fmt::Display for RefundError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RefundError::NothingToRefund => f.write_str("no payables to refund"),
+            RefundError::InvalidAmount(amount) => write!(f, "cannot refund {amount}, refunds must be positive"),
+            RefundError::MixedTransactions => f.write_str("payables belong to more than one transaction"),
+            RefundError::MerchantMismatch { expected, found } => {
+                write!(f, "transaction belongs to merchant {found:?}, not {expected:?}")
+            }
+            RefundError::ExceedsRemaining { requested, remaining } => {
+                write!(f, "cannot refund {requested}, only {remaining} is left to refund")
+            }
+            RefundError::Money(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for RefundError {}
+
+impl From<MoneyError> for RefundError {
+    fn from(err: MoneyError) -> Self {
+        RefundError::Money(err)
+    }
+}
+
+/// The outcome of a refund: how much went back to the cardholder, how much of the fee went
+/// back to the merchant, and the negative payables that claw back funds already paid.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Refund {
+    pub amount: Money,
+    pub fee_returned: Money,
+    pub adjustments: Vec<Payable>,
+}
+
+/// Refunds transactions that have already become payables, in full or in as many partial
+/// refunds as the captured amount of the transaction allows.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct Refunder {
+    fees: FeeRefund,
+    rounding: RoundingMode,
+}
+
+impl Refunder {
+    pub fn new(fees: FeeRefund) -> Self {
+        Refunder { fees, rounding: RoundingMode::default() }
+    }
+
+    pub fn with_rounding(mut self, rounding: RoundingMode) -> Self {
+        self.rounding = rounding;
+        self
+    }
+
+    /// How much of the transaction behind `payables` can still be refunded.
+    pub fn remaining(&self, payables: &[Payable]) -> Result<Money, RefundError> {
+        let first = payables.first().ok_or(RefundError::NothingToRefund)?;
+        let tx = first.transaction();
+        if payables.iter().any(|payable| payable.transaction_id() != tx.id()) {
+            return Err(RefundError::MixedTransactions);
+        }
+        let currency = first.currency();
+        let refunded = Money::sum(payables.iter().map(Payable::refunded), currency)?;
+        let chargedback = Money::sum(payables.iter().map(Payable::chargedback), currency)?;
+        let refundable = Money::sum(payables.iter().map(|payable| refundable(payable, payables)), currency)?;
+        let left = tx.captured().checked_sub(refunded)?.checked_sub(chargedback)?;
+        Ok(if left.checked_cmp(&refundable)? == Ordering::Less { left } else { refundable })
+    }
+
+    /// Refunds `amount` out of `payables`, which must be every payable of one transaction.
+    /// Funds still waiting are reduced first, starting from the last installment, and
+    /// whatever is left comes back from paid, then anticipated, funds through negative
+    /// payables based on their original gross and fee, due today for `merchant`, who has to
+    /// be the one the transaction was made for. Nothing is changed if the refund would exceed
+    /// what is left to refund.
+    pub fn refund(&self, payables: &mut [Payable], amount: Money, merchant: &Merchant, clock: &impl Clock) -> Result<Refund, RefundError> {
+        let remaining = self.remaining(payables)?;
+        let tx = payables[0].transaction();
+        if tx.merchant_id() != merchant.id() {
+            return Err(RefundError::MerchantMismatch { expected: merchant.id().to_owned(), found: tx.merchant_id().to_owned() });
+        }
+        if amount.checked_cmp(&Money::zero(remaining.currency()))? != Ordering::Greater {
+            return Err(RefundError::InvalidAmount(amount));
+        }
+        if amount.checked_cmp(&remaining)? == Ordering::Greater {
+            return Err(RefundError::ExceedsRemaining { requested: amount, remaining });
+        }
+
+        let now = clock.now();
+        let today = merchant.local_date(now);
+        let mut left = amount;
+        let mut refund = Refund { amount, fee_returned: Money::zero(amount.currency()), adjustments: Vec::new() };
+        let available: Vec<_> = payables.iter().map(|payable| refundable(payable, payables)).collect();
+        for status in [PayableStatus::WaitingFunds, PayableStatus::Paid, PayableStatus::Anticipated] {
+            let candidates = payables.iter_mut().zip(available.iter().copied()).rev().filter(|(payable, _)| *payable.status() == status);
+            for (payable, available) in candidates {
+                if left.is_zero() {
+                    return Ok(refund);
+                }
+                if available.is_zero() {
+                    continue;
+                }
+                let taken = if left.checked_cmp(&available)? == Ordering::Less { left } else { available };
+                let fee_returned = self.fee_returned(payable, taken)?;
+                payable.record_refund(taken, fee_returned)?;
+                if status != PayableStatus::WaitingFunds {
+                    let adjustment = Payable::new(
+                        PayableStatus::WaitingFunds,
+                        payable.transaction().clone(),
+                        payable.installment(),
+                        taken.checked_neg()?,
+                        fee_returned.checked_neg()?,
+                        today,
+                        None,
+                    )?;
+                    refund.adjustments.push(adjustment);
+                }
+                // anticipated funds stay anticipated, the refund is only tracked on them
+                let fully_refunded = match status {
+                    PayableStatus::WaitingFunds => payable.gross().is_zero() && payable.fee().is_zero(),
+                    PayableStatus::Paid => payable.refunded() == payable.gross(),
+                    _ => false,
+                };
+                if fully_refunded {
+                    payable
+                        .transition_to(PayableStatus::Refunded, now)
+                        .expect("funds waiting or paid can always be refunded");
+                }
+                refund.fee_returned = refund.fee_returned.checked_add(fee_returned)?;
+                left = left.checked_sub(taken)?;
+            }
+        }
+        Ok(refund)
+    }
+
+    /// Refunds everything that is left to refund of the transaction behind `payables`.
+    pub fn refund_in_full(&self, payables: &mut [Payable], merchant: &Merchant, clock: &impl Clock) -> Result<Refund, RefundError> {
+        let remaining = self.remaining(payables)?;
+        self.refund(payables, remaining, merchant, clock)
+    }
+
+    /// The part of the fee of `payable` that goes back to the merchant with `amount`. Paid
+    /// payables work it out on the running total refunded so that partial refunds add up to
+    /// exactly the whole fee.
+    fn fee_returned(&self, payable: &Payable, amount: Money) -> Result<Money, MoneyError> {
+        match self.fees {
+            FeeRefund::Retained => Ok(Money::zero(amount.currency())),
+            FeeRefund::Proportional if *payable.status() == PayableStatus::WaitingFunds => {
+                payable.fee().scale(amount.amount(), payable.gross().amount(), self.rounding)
+            }
+            FeeRefund::Proportional => {
+                let gross = payable.gross().amount();
+                let before = payable.fee().scale(payable.refunded().amount(), gross, self.rounding)?;
+                let after = payable.fee().scale(payable.refunded().checked_add(amount)?.amount(), gross, self.rounding)?;
+                after.checked_sub(before)
+            }
+        }
+    }
+}
+
+/// What can still be refunded out of `payable`: whatever was neither refunded nor charged
+/// back yet while the funds are waiting, paid or anticipated. Adjustments are negative, so
+/// there is nothing to refund out of them, and the payout of an anticipation is refunded
+/// through the anticipated payable in `payables` it stands for.
+fn refundable(payable: &Payable, payables: &[Payable]) -> Money {
+    let zero = Money::zero(payable.currency());
+    let payout = *payable.status() == PayableStatus::Paid
+        && payables.iter().any(|other| {
+            *other.status() == PayableStatus::Anticipated
+                && other.transaction_id() == payable.transaction_id()
+                && other.installment() == payable.installment()
+        });
+    let available = if payout { zero } else { payable.outstanding().unwrap_or(zero) };
+    if available.amount() > 0 { available } else { zero }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::anticipation::Anticipator;
+    use crate::money::Rate;
+    use crate::test_support::{brl, clock_on, date, installments, merchant, merchant_named, payables};
+    use crate::transaction::PaymentMethod;
+
+    #[test]
+    fn should_reduce_pending_installments_starting_from_the_last_one() {
+        // three installments of 100.00, each with a 5.00 fee
+        let mut payables = payables(&merchant(), PaymentMethod::Credit, brl(30000), 3);
+        let refunder = Refunder::new(FeeRefund::Proportional);
+
+        let refund = refunder.refund(&mut payables, brl(15000), &merchant(), &clock_on(2024, 3, 20)).unwrap();
+
+        assert_eq!(refund.fee_returned, brl(750));
+        assert!(refund.adjustments.is_empty());
+        assert_eq!(*payables[2].status(), PayableStatus::Refunded);
+        assert_eq!((payables[1].gross(), payables[1].fee(), payables[1].net()), (brl(5000), brl(250), brl(4750)));
+        assert_eq!(payables[0].gross(), brl(10000));
+        assert_eq!(refunder.remaining(&payables), Ok(brl(15000)));
+    }
+
+    #[test]
+    fn should_claw_back_paid_funds_through_negative_payables() {
+        // paid right away, with a 3.00 fee
+        let mut payables = payables(&merchant(), PaymentMethod::Debit, brl(10000), 1);
+        let refunder = Refunder::new(FeeRefund::Proportional);
+        let clock = clock_on(2024, 3, 20);
+
+        let first = refunder.refund(&mut payables, brl(4000), &merchant(), &clock).unwrap();
+        let second = refunder.refund_in_full(&mut payables, &merchant(), &clock).unwrap();
+
+        let adjustments: Vec<_> = [&first, &second]
+            .iter()
+            .flat_map(|refund| &refund.adjustments)
+            .map(|payable| (payable.gross(), payable.fee(), payable.net()))
+            .collect();
+        assert_eq!(adjustments, vec![(brl(-4000), brl(-120), brl(-3880)), (brl(-6000), brl(-180), brl(-5820))]);
+        let adjustment = &second.adjustments[0];
+        assert_eq!(*adjustment.status(), PayableStatus::WaitingFunds);
+        assert_eq!(adjustment.due_date(), date(2024, 3, 20));
+        assert_eq!(adjustment.transaction_id(), payables[0].transaction_id());
+        assert_eq!(*payables[0].status(), PayableStatus::Refunded);
+        assert_eq!(payables[0].refunded(), brl(10000));
+    }
+
+    #[test]
+    fn should_let_the_psp_keep_the_fee_when_it_is_retained() {
+        let mut payables = payables(&merchant(), PaymentMethod::Debit, brl(10000), 1);
+        let refunder = Refunder::new(FeeRefund::Retained);
+
+        let refund = refunder.refund_in_full(&mut payables, &merchant(), &clock_on(2024, 3, 20)).unwrap();
+
+        assert_eq!(refund.fee_returned, brl(0));
+        assert_eq!(refund.adjustments[0].net(), brl(-10000));
+    }
+
+    #[test]
+    fn should_refuse_refunds_beyond_what_is_left() {
+        let mut payables = payables(&merchant(), PaymentMethod::Credit, brl(30000), 3);
+        let refunder = Refunder::new(FeeRefund::Proportional);
+        let clock = clock_on(2024, 3, 20);
+
+        assert_eq!(
+            refunder.refund(&mut payables, brl(0), &merchant(), &clock).unwrap_err(),
+            RefundError::InvalidAmount(brl(0))
+        );
+        refunder.refund(&mut payables, brl(20000), &merchant(), &clock).unwrap();
+        assert_eq!(
+            refunder.refund(&mut payables, brl(10001), &merchant(), &clock).unwrap_err(),
+            RefundError::ExceedsRemaining { requested: brl(10001), remaining: brl(10000) }
+        );
+        assert_eq!(payables[0].gross(), brl(10000));
+
+        let mut mixed = payables;
+        mixed.extend(self::payables(&merchant(), PaymentMethod::Debit, brl(10000), 1));
+        assert_eq!(refunder.remaining(&mixed), Err(RefundError::MixedTransactions));
+    }
+
+    #[test]
+    fn should_refuse_to_refund_on_behalf_of_another_merchant() {
+        let mut payables = payables(&merchant(), PaymentMethod::Debit, brl(10000), 1);
+        let refunder = Refunder::new(FeeRefund::Proportional);
+
+        assert_eq!(
+            refunder.refund(&mut payables, brl(4000), &merchant_named("merchant-2"), &clock_on(2024, 3, 20)).unwrap_err(),
+            RefundError::MerchantMismatch { expected: "merchant-2".to_owned(), found: "merchant-1".to_owned() }
+        );
+        assert_eq!(payables[0].refunded(), brl(0));
+    }
+
+    #[test]
+    fn should_claw_back_anticipated_funds_in_full() {
+        let mut payables = installments();
+        let anticipation = Anticipator::new(Rate::from_basis_points(200)).anticipate(&mut payables, &merchant(), &clock_on(2024, 3, 16)).unwrap();
+        payables.extend(anticipation.payables);
+        let refunder = Refunder::new(FeeRefund::Proportional);
+        assert_eq!(refunder.remaining(&payables), Ok(brl(10000)));
+
+        let refund = refunder.refund_in_full(&mut payables, &merchant(), &clock_on(2024, 3, 20)).unwrap();
+
+        let adjustments: Vec<_> = refund.adjustments.iter().map(|payable| (payable.gross(), payable.fee(), payable.net())).collect();
+        assert_eq!(adjustments, vec![(brl(-5000), brl(-250), brl(-4750)), (brl(-5000), brl(-250), brl(-4750))]);
+        assert!(payables[..2].iter().all(|payable| *payable.status() == PayableStatus::Anticipated && payable.refunded() == brl(5000)));
+        assert_eq!(refunder.remaining(&payables), Ok(brl(0)));
+    }
+}
@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+
+use crate::clock::Clock;
+use crate::merchant::Merchant;
+use crate::money::{Currency, Money, MoneyError};
+use crate::payable::{Payable, PayableStatus};
+
+/// Where the settlement job finds the payables of each merchant.
+pub trait PayableStore {
+    fn merchants(&self) -> Vec<Merchant>;
+
+    fn payables_mut(&mut self, merchant_id: &str) -> &mut [Payable];
+}
+
+#[derive(Debug, Default)]
+pub struct InMemoryPayableStore {
+    merchants: Vec<Merchant>,
+    payables: HashMap<String, Vec<Payable>>,
+}
+
+impl InMemoryPayableStore {
+    pub fn new() -> Self {
+        InMemoryPayableStore::default()
+    }
+
+    pub fn add(&mut self, merchant: &Merchant, payables: impl IntoIterator<Item = Payable>) {
+        if !self.merchants.iter().any(|known| known.id() == merchant.id()) {
+            self.merchants.push(merchant.clone());
+        }
+        self.payables.entry(merchant.id().to_owned()).or_default().extend(payables);
+    }
+
+    pub fn payables(&self, merchant_id: &str) -> &[Payable] {
+        self.payables.get(merchant_id).map(Vec::as_slice).unwrap_or_default()
+    }
+}
+
+impl PayableStore for InMemoryPayableStore {
+    fn merchants(&self) -> Vec<Merchant> {
+        self.merchants.clone()
+    }
+
+    fn payables_mut(&mut self, merchant_id: &str) -> &mut [Payable] {
+        self.payables.get_mut(merchant_id).map(Vec::as_mut_slice).unwrap_or_default()
+    }
+}
+
+/// What one merchant received in one currency on a settlement day.
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MerchantSettlement {
+    pub merchant_id: String,
+    pub date: NaiveDate,
+    pub count: usize,
+    pub gross: Money,
+    pub fees: Money,
+    pub net: Money,
+}
+
+#[derive(PartialEq, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SettlementReport {
+    pub merchants: Vec<MerchantSettlement>,
+}
+
+impl SettlementReport {
+    pub fn for_merchant(&self, merchant_id: &str, currency: Currency) -> Option<&MerchantSettlement> {
+        self.merchants
+            .iter()
+            .find(|settlement| settlement.merchant_id == merchant_id && settlement.gross.currency() == currency)
+    }
+
+    fn add(&mut self, merchant_id: &str, date: NaiveDate, payable: &Payable) -> Result<(), MoneyError> {
+        let gross = payable.gross();
+        let position = self
+            .merchants
+            .iter()
+            .position(|settlement| settlement.merchant_id == merchant_id && settlement.gross.currency() == gross.currency());
+        let settlement = match position {
+            Some(position) => &mut self.merchants[position],
+            None => {
+                let zero = Money::zero(gross.currency());
+                self.merchants.push(MerchantSettlement { merchant_id: merchant_id.to_owned(), date, count: 0, gross: zero, fees: zero, net: zero });
+                self.merchants.last_mut().expect("just pushed")
+            }
+        };
+        settlement.count += 1;
+        settlement.gross = settlement.gross.checked_add(gross)?;
+        settlement.fees = settlement.fees.checked_add(payable.fee())?;
+        settlement.net = settlement.net.checked_add(payable.net())?;
+        Ok(())
+    }
+}
+
+/// Pays out every `WaitingFunds` payable due on or before today, today being the date in
+/// each merchant's own timezone. Payables on hold wait until the hold is released.
+///
+/// The report covers every payable the job has settled for that day, including the ones
+/// settled by an earlier run, so running it twice for the same day settles nothing new
+/// and reports the same totals.
+pub fn settle_due_payables(store: &mut impl PayableStore, clock: &impl Clock) -> Result<SettlementReport, MoneyError> {
+    let now = clock.now();
+    let mut report = SettlementReport::default();
+    for merchant in store.merchants() {
+        let today = merchant.local_date(now);
+        for payable in store.payables_mut(merchant.id()) {
+            if *payable.status() == PayableStatus::WaitingFunds && !payable.is_on_hold() && payable.due_date() <= today {
+                payable.settle(today, now).expect("funds waiting can always be paid");
+            }
+            if settled_by_job_on(payable, today) {
+                report.add(merchant.id(), today, payable)?;
+            }
+        }
+    }
+    Ok(report)
+}
+
+fn settled_by_job_on(payable: &Payable, date: NaiveDate) -> bool {
+    *payable.status() == PayableStatus::Paid
+        && payable.settlement_date() == Some(date)
+        && payable.history().iter().any(|change| change.from == PayableStatus::WaitingFunds && change.to == PayableStatus::Paid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{brl, clock_on, date, merchant_named, payables};
+    use crate::transaction::PaymentMethod;
+
+    #[test]
+    fn should_pay_out_only_the_payables_that_are_due() {
+        let (first, second) = (merchant_named("merchant-1"), merchant_named("merchant-2"));
+        let mut store = InMemoryPayableStore::new();
+        store.add(&first, payables(&first, PaymentMethod::Credit, brl(10000), 2));
+        store.add(&second, payables(&second, PaymentMethod::Credit, brl(20000), 1));
+
+        let report = settle_due_payables(&mut store, &clock_on(2024, 4, 15)).unwrap();
+
+        let statuses: Vec<_> = store.payables("merchant-1").iter().map(|payable| *payable.status()).collect();
+        assert_eq!(statuses, vec![PayableStatus::Paid, PayableStatus::WaitingFunds]);
+        assert_eq!(store.payables("merchant-1")[0].settlement_date(), Some(date(2024, 4, 15)));
+        assert_eq!(
+            report.for_merchant("merchant-1", Currency::BRL),
+            Some(&MerchantSettlement {
+                merchant_id: "merchant-1".to_owned(),
+                date: date(2024, 4, 15),
+                count: 1,
+                gross: brl(5000),
+                fees: brl(250),
+                net: brl(4750),
+            })
+        );
+        assert_eq!(report.for_merchant("merchant-2", Currency::BRL).map(|settlement| settlement.net), Some(brl(19000)));
+    }
+
+    #[test]
+    fn should_settle_nothing_new_when_run_twice_on_the_same_day() {
+        let first = merchant_named("merchant-1");
+        let mut store = InMemoryPayableStore::new();
+        store.add(&first, payables(&first, PaymentMethod::Credit, brl(10000), 2));
+        let clock = clock_on(2024, 4, 15);
+
+        let report = settle_due_payables(&mut store, &clock).unwrap();
+        let again = settle_due_payables(&mut store, &clock).unwrap();
+
+        assert_eq!(report, again);
+        assert_eq!(store.payables("merchant-1")[0].history().len(), 1);
+    }
+
+    #[test]
+    fn should_report_nothing_before_anything_is_due() {
+        let first = merchant_named("merchant-1");
+        let mut store = InMemoryPayableStore::new();
+        store.add(&first, payables(&first, PaymentMethod::Credit, brl(10000), 1));
+
+        let report = settle_due_payables(&mut store, &clock_on(2024, 4, 14)).unwrap();
+
+        assert_eq!(report, SettlementReport::default());
+        assert_eq!(*store.payables("merchant-1")[0].status(), PayableStatus::WaitingFunds);
+    }
+}
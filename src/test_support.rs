@@ -0,0 +1,62 @@
+//! Factories shared by the unit tests, so every module builds the same merchant, card and
+//! transactions.
+
+use chrono::{NaiveDate, TimeZone, Utc};
+
+use crate::acquirer::AuthorizationResult;
+use crate::card::Card;
+use crate::clock::FixedClock;
+use crate::merchant::Merchant;
+use crate::money::{Currency, Money};
+use crate::payable::Payable;
+use crate::transaction::{PaymentMethod, Transaction};
+
+pub(crate) fn brl(amount: i64) -> Money {
+    Money::from_minor(amount, Currency::BRL)
+}
+
+pub(crate) fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).unwrap()
+}
+
+/// `merchant-1`, in São Paulo.
+pub(crate) fn merchant() -> Merchant {
+    merchant_named("merchant-1")
+}
+
+pub(crate) fn merchant_named(id: &str) -> Merchant {
+    Merchant::new(id, chrono_tz::America::Sao_Paulo)
+}
+
+/// Noon UTC on the given day, 09:00 in São Paulo.
+pub(crate) fn clock_on(year: i32, month: u32, day: u32) -> FixedClock {
+    FixedClock::new(Utc.with_ymd_and_hms(year, month, day, 12, 0, 0).unwrap())
+}
+
+/// A Visa card approved by the simulated acquirer.
+pub(crate) fn card() -> Card {
+    card_numbered("4111111111111111")
+}
+
+pub(crate) fn card_numbered(number: &str) -> Card {
+    Card::try_new(number.to_owned(), "Rafael Dias".to_owned(), "12/30".to_owned()).unwrap()
+}
+
+pub(crate) fn approval() -> AuthorizationResult {
+    AuthorizationResult::approved("000001", "000000001", "00").unwrap()
+}
+
+/// The payables of an approved sale of `value` to `merchant` made on 2024-03-15, split into
+/// `installments`.
+pub(crate) fn payables(merchant: &Merchant, method: PaymentMethod, value: Money, installments: u8) -> Vec<Payable> {
+    let tx = Transaction::try_new_at(merchant, value, "Test Transaction".to_owned(), method, card(), approval(), &clock_on(2024, 3, 15))
+        .unwrap()
+        .with_installments(installments)
+        .unwrap();
+    Payable::from_transaction(tx, merchant).unwrap()
+}
+
+/// Two installments of 50.00 with a 2.50 fee each, due on 2024-04-15 and 2024-05-15.
+pub(crate) fn installments() -> Vec<Payable> {
+    payables(&merchant(), PaymentMethod::Credit, brl(10000), 2)
+}
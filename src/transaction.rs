@@ -0,0 +1,639 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+
+use crate::acquirer::AuthorizationResult;
+use crate::card::{Card, Expiry};
+use crate::clock::{Clock, SystemClock};
+use crate::merchant::Merchant;
+use crate::money::{Currency, Money};
+use crate::Unmasked;
+
+const MAX_INSTALLMENTS: u8 = 12;
+pub const DEFAULT_CAPTURE_WINDOW_DAYS: i64 = 7;
+
+/// Identifies a transaction, and every payable derived from it, across services.
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
+pub struct TransactionId(Uuid);
+
+impl TransactionId {
+    fn new() -> Self {
+        TransactionId(Uuid::new_v4())
+    }
+
+    pub fn as_uuid(&self) -> &Uuid {
+        &self.0
+    }
+}
+
+impl fmt::Display for TransactionId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl FromStr for TransactionId {
+    type Err = uuid::Error;
+
+    fn from_str(id: &str) -> Result<Self, Self::Err> {
+        Uuid::parse_str(id).map(TransactionId)
+    }
+}
+
+#[derive(Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "TransactionRecord", try_from = "TransactionRecord")
+)]
+pub struct Transaction {
+    id: TransactionId,
+    merchant_id: String,
+    external_reference: Option<String>,
+    value: Money,
+    description: String,
+    method: PaymentMethod,
+    card: Card,
+    created_at: DateTime<Utc>,
+    installments: u8,
+    status: TransactionStatus,
+    captured: Money,
+    capture_deadline: Option<DateTime<Utc>>,
+    authorization: Option<AuthorizationResult>,
+}
+
+/// Where a transaction is between authorizing the card and capturing the funds.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
+#[non_exhaustive]
+pub enum TransactionStatus {
+    /// The funds are reserved on the card, waiting to be captured.
+    Authorized,
+    Captured,
+    Voided,
+    /// The authorization was not captured in time and the reservation lapsed.
+    Expired,
+    /// The issuer or the acquirer turned the authorization down.
+    Declined,
+}
+
+impl TransactionStatus {
+    /// Whether a transaction may move from this status to `to`. Only an authorization
+    /// still waiting can be captured, voided, left to expire or declined.
+    pub fn can_transition_to(&self, to: TransactionStatus) -> bool {
+        use TransactionStatus::*;
+        matches!((self, to), (Authorized, Captured | Voided | Expired | Declined))
+    }
+}
+
+#[derive(PartialEq, Debug)]
+#[non_exhaustive]
+pub enum TransactionError {
+    ExpiredCard { expiry: Expiry },
+    UnknownCardExpiry,
+    InvalidInstallments { method: PaymentMethod, installments: u8 },
+    IllegalTransition { from: TransactionStatus, to: TransactionStatus },
+    AuthorizationExpired { deadline: DateTime<Utc> },
+    InvalidCaptureAmount { amount: Money, authorized: Money },
+    AlreadyAuthorized,
+    NotApproved,
+    InconsistentStatus { status: TransactionStatus },
+}
+
+impl fmt::Display for TransactionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransactionError::ExpiredCard { expiry } => write!(f, "card expired in {expiry}"),
+            TransactionError::UnknownCardExpiry => f.write_str("card expiry is unknown, a stored card cannot be charged again"),
+            TransactionError::InvalidInstallments { method, installments } => {
+                write!(f, "{method:?} transactions cannot be split into {installments} installments")
+            }
+            TransactionError::IllegalTransition { from, to } => write!(f, "a {from:?} transaction cannot become {to:?}"),
+            TransactionError::AuthorizationExpired { deadline } => write!(f, "authorization expired at {deadline}"),
+            TransactionError::InvalidCaptureAmount { amount, authorized } => {
+                write!(f, "cannot capture {amount} out of {authorized} authorized")
+            }
+            TransactionError::AlreadyAuthorized => write!(f, "the acquirer already answered this authorization"),
+            TransactionError::NotApproved => f.write_str("only an authorization the acquirer approved can be captured"),
+            TransactionError::InconsistentStatus { status } => {
+                write!(f, "a {status:?} transaction does not match its authorization and captured amount")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TransactionError {}
+
+impl Transaction {
+    /// Records a sale for `merchant` with a freshly generated ID that the acquirer
+    /// authorized and captured in one step, as `authorization` says: approved sales are
+    /// captured in full, declined ones are `Declined`. Sales that still have to go to the
+    /// acquirer are built with `authorize` and run through `Processor::sale`.
+    pub fn try_new(
+        merchant: &Merchant,
+        value: Money,
+        description: String,
+        method: PaymentMethod,
+        card: Card,
+        authorization: AuthorizationResult,
+    ) -> Result<Self, TransactionError> {
+        Transaction::try_new_at(merchant, value, description, method, card, authorization, &SystemClock)
+    }
+
+    /// Same as `try_new`, but timestamps the transaction and checks the card expiry with
+    /// `clock` instead of the system time.
+    pub fn try_new_at(
+        merchant: &Merchant,
+        value: Money,
+        description: String,
+        method: PaymentMethod,
+        card: Card,
+        authorization: AuthorizationResult,
+        clock: &impl Clock,
+    ) -> Result<Self, TransactionError> {
+        let mut tx = Transaction::authorize_at(merchant, value, description, method, card, clock)?.with_authorization(authorization)?;
+        if tx.is_approved() {
+            tx.capture(value, clock)?;
+        }
+        tx.capture_deadline = None;
+        Ok(tx)
+    }
+
+    /// Authorizes `value` on the card without capturing it, e.g. when a hotel guest checks
+    /// in. The funds have to be captured within the capture window, which defaults to
+    /// `DEFAULT_CAPTURE_WINDOW_DAYS`, or the authorization expires.
+    pub fn authorize(merchant: &Merchant, value: Money, description: String, method: PaymentMethod, card: Card) -> Result<Self, TransactionError> {
+        Transaction::authorize_at(merchant, value, description, method, card, &SystemClock)
+    }
+
+    /// Same as `authorize`, but timestamps the authorization and checks the card expiry with
+    /// `clock` instead of the system time.
+    pub fn authorize_at(
+        merchant: &Merchant,
+        value: Money,
+        description: String,
+        method: PaymentMethod,
+        card: Card,
+        clock: &impl Clock,
+    ) -> Result<Self, TransactionError> {
+        let created_at = clock.now();
+        let expiry = card.expires_at().ok_or(TransactionError::UnknownCardExpiry)?;
+        if expiry.is_expired(created_at.date_naive()) {
+            return Err(TransactionError::ExpiredCard { expiry });
+        }
+        Ok(Transaction {
+            id: TransactionId::new(),
+            merchant_id: merchant.id().to_owned(),
+            external_reference: None,
+            value,
+            description,
+            method,
+            card,
+            created_at,
+            installments: 1,
+            status: TransactionStatus::Authorized,
+            captured: Money::zero(value.currency()),
+            capture_deadline: Some(created_at + Duration::days(DEFAULT_CAPTURE_WINDOW_DAYS)),
+            authorization: None,
+        })
+    }
+
+    /// Gives an authorization `window` from its creation to be captured instead of the
+    /// default one.
+    pub fn with_capture_window(mut self, window: Duration) -> Self {
+        if self.status == TransactionStatus::Authorized {
+            self.capture_deadline = Some(self.created_at + window);
+        }
+        self
+    }
+
+    /// Captures `amount` out of the authorized value, which may be less than all of it when
+    /// the final bill is smaller. The rest of the authorization is released.
+    pub fn capture(&mut self, amount: Money, clock: &impl Clock) -> Result<(), TransactionError> {
+        self.check_transition(TransactionStatus::Captured)?;
+        if !self.is_approved() {
+            return Err(TransactionError::NotApproved);
+        }
+        if self.expire_if_due(clock) {
+            let deadline = self.capture_deadline.expect("authorizations have a deadline");
+            return Err(TransactionError::AuthorizationExpired { deadline });
+        }
+        let positive = amount.checked_cmp(&Money::zero(self.value.currency())) == Ok(Ordering::Greater);
+        let within = matches!(amount.checked_cmp(&self.value), Ok(Ordering::Less | Ordering::Equal));
+        if !positive || !within {
+            return Err(TransactionError::InvalidCaptureAmount { amount, authorized: self.value });
+        }
+        self.captured = amount;
+        self.status = TransactionStatus::Captured;
+        Ok(())
+    }
+
+    /// Cancels an authorization that will not be captured, releasing the funds on the card.
+    pub fn void(&mut self) -> Result<(), TransactionError> {
+        self.check_transition(TransactionStatus::Voided)?;
+        self.status = TransactionStatus::Voided;
+        Ok(())
+    }
+
+    /// Lets the authorization lapse if it was not captured before its deadline. Returns
+    /// whether it did.
+    pub fn expire_if_due(&mut self, clock: &impl Clock) -> bool {
+        let due = self.status == TransactionStatus::Authorized && self.capture_deadline.is_some_and(|deadline| clock.now() >= deadline);
+        if due {
+            self.status = TransactionStatus::Expired;
+        }
+        due
+    }
+
+    fn check_transition(&self, to: TransactionStatus) -> Result<(), TransactionError> {
+        if !self.status.can_transition_to(to) {
+            return Err(TransactionError::IllegalTransition { from: self.status, to });
+        }
+        Ok(())
+    }
+
+    /// Splits a credit transaction into `installments` monthly payments (parcelas).
+    pub fn with_installments(mut self, installments: u8) -> Result<Self, TransactionError> {
+        let allowed = match self.method {
+            PaymentMethod::Credit => 1..=MAX_INSTALLMENTS,
+            PaymentMethod::Debit => 1..=1,
+        };
+        if !allowed.contains(&installments) {
+            return Err(TransactionError::InvalidInstallments { method: self.method, installments });
+        }
+        self.installments = installments;
+        Ok(self)
+    }
+
+    /// Records the acquirer's answer to the authorization request. A decline moves the
+    /// authorization to `Declined`; only an approved one can be captured and turned into
+    /// payables.
+    pub fn with_authorization(mut self, result: AuthorizationResult) -> Result<Self, TransactionError> {
+        self.check_authorizable()?;
+        if !result.is_approved() {
+            self.status = TransactionStatus::Declined;
+            self.capture_deadline = None;
+        }
+        self.authorization = Some(result);
+        Ok(self)
+    }
+
+    /// Whether the acquirer's answer can still be recorded: only once, and only on an
+    /// authorization nothing else has happened to yet.
+    pub(crate) fn check_authorizable(&self) -> Result<(), TransactionError> {
+        if self.authorization.is_some() {
+            return Err(TransactionError::AlreadyAuthorized);
+        }
+        if self.status != TransactionStatus::Authorized {
+            return Err(TransactionError::IllegalTransition { from: self.status, to: TransactionStatus::Authorized });
+        }
+        Ok(())
+    }
+
+    /// Attaches the merchant's own identifier for the transaction, e.g. an order number.
+    pub fn with_external_reference(mut self, reference: impl Into<String>) -> Self {
+        self.external_reference = Some(reference.into());
+        self
+    }
+
+    pub fn id(&self) -> TransactionId {
+        self.id
+    }
+
+    pub fn merchant_id(&self) -> &str {
+        &self.merchant_id
+    }
+
+    pub fn external_reference(&self) -> Option<&str> {
+        self.external_reference.as_deref()
+    }
+
+    pub fn value(&self) -> Money {
+        self.value
+    }
+
+    pub fn currency(&self) -> Currency {
+        self.value.currency()
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    pub fn method(&self) -> &PaymentMethod {
+        &self.method
+    }
+
+    pub fn card(&self) -> &Card {
+        &self.card
+    }
+
+    pub fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+
+    pub fn installments(&self) -> u8 {
+        self.installments
+    }
+
+    pub fn status(&self) -> TransactionStatus {
+        self.status
+    }
+
+    /// How much of the value has been captured, which is what the merchant gets paid on.
+    pub fn captured(&self) -> Money {
+        self.captured
+    }
+
+    /// When an authorization still waiting to be captured expires.
+    pub fn capture_deadline(&self) -> Option<DateTime<Utc>> {
+        self.capture_deadline
+    }
+
+    /// What the acquirer answered to the authorization, once it has.
+    pub fn authorization(&self) -> Option<&AuthorizationResult> {
+        self.authorization.as_ref()
+    }
+
+    pub fn is_approved(&self) -> bool {
+        self.authorization.as_ref().is_some_and(AuthorizationResult::is_approved)
+    }
+
+    pub fn unmasked(&self) -> Unmasked<'_, Transaction> {
+        Unmasked(self)
+    }
+}
+
+impl fmt::Debug for Transaction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Transaction")
+            .field("id", &self.id)
+            .field("merchant_id", &self.merchant_id)
+            .field("external_reference", &self.external_reference)
+            .field("value", &self.value)
+            .field("description", &self.description)
+            .field("method", &self.method)
+            .field("card", &self.card)
+            .field("created_at", &self.created_at)
+            .field("installments", &self.installments)
+            .field("status", &self.status)
+            .field("captured", &self.captured)
+            .field("capture_deadline", &self.capture_deadline)
+            .field("authorization", &self.authorization)
+            .finish()
+    }
+}
+
+impl fmt::Display for Transaction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} by {:?} on {}", self.value, self.method, self.card)
+    }
+}
+
+impl fmt::Debug for Unmasked<'_, Transaction> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Transaction")
+            .field("id", &self.0.id)
+            .field("merchant_id", &self.0.merchant_id)
+            .field("external_reference", &self.0.external_reference)
+            .field("value", &self.0.value)
+            .field("description", &self.0.description)
+            .field("method", &self.0.method)
+            .field("card", &self.0.card.unmasked())
+            .field("created_at", &self.0.created_at)
+            .field("installments", &self.0.installments)
+            .field("status", &self.0.status)
+            .field("captured", &self.0.captured)
+            .field("capture_deadline", &self.0.capture_deadline)
+            .field("authorization", &self.0.authorization)
+            .finish()
+    }
+}
+
+/// What a transaction is serialized as. Deserializing checks the record the way the
+/// constructors and `capture` would, so a payload cannot capture more than the value, in
+/// another currency, or without an approval.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct TransactionRecord {
+    id: TransactionId,
+    merchant_id: String,
+    external_reference: Option<String>,
+    value: Money,
+    description: String,
+    method: PaymentMethod,
+    card: Card,
+    created_at: DateTime<Utc>,
+    installments: u8,
+    status: TransactionStatus,
+    captured: Money,
+    capture_deadline: Option<DateTime<Utc>>,
+    authorization: Option<AuthorizationResult>,
+}
+
+#[cfg(feature = "serde")]
+impl From<Transaction> for TransactionRecord {
+    fn from(tx: Transaction) -> Self {
+        TransactionRecord {
+            id: tx.id,
+            merchant_id: tx.merchant_id,
+            external_reference: tx.external_reference,
+            value: tx.value,
+            description: tx.description,
+            method: tx.method,
+            card: tx.card,
+            created_at: tx.created_at,
+            installments: tx.installments,
+            status: tx.status,
+            captured: tx.captured,
+            capture_deadline: tx.capture_deadline,
+            authorization: tx.authorization,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<TransactionRecord> for Transaction {
+    type Error = TransactionError;
+
+    fn try_from(record: TransactionRecord) -> Result<Self, Self::Error> {
+        let zero = Money::zero(record.value.currency());
+        let within = matches!(record.captured.checked_cmp(&record.value), Ok(Ordering::Less | Ordering::Equal));
+        if !within || record.captured.checked_cmp(&zero) == Ok(Ordering::Less) {
+            return Err(TransactionError::InvalidCaptureAmount { amount: record.captured, authorized: record.value });
+        }
+        let approved = record.authorization.as_ref().map(AuthorizationResult::is_approved);
+        let consistent = match record.status {
+            TransactionStatus::Captured => approved == Some(true) && record.captured != zero,
+            TransactionStatus::Declined => approved == Some(false) && record.captured == zero,
+            _ => approved != Some(false) && record.captured == zero,
+        };
+        if !consistent {
+            return Err(TransactionError::InconsistentStatus { status: record.status });
+        }
+        let tx = Transaction {
+            id: record.id,
+            merchant_id: record.merchant_id,
+            external_reference: record.external_reference,
+            value: record.value,
+            description: record.description,
+            method: record.method,
+            card: record.card,
+            created_at: record.created_at,
+            installments: 1,
+            status: record.status,
+            captured: record.captured,
+            capture_deadline: record.capture_deadline,
+            authorization: record.authorization,
+        };
+        tx.with_installments(record.installments)
+    }
+}
+
+/// Lets every authorization in `transactions` that was not captured in time expire, and
+/// returns how many did.
+pub fn expire_authorizations<'a>(transactions: impl IntoIterator<Item = &'a mut Transaction>, clock: &impl Clock) -> usize {
+    transactions.into_iter().map(|tx| tx.expire_if_due(clock)).filter(|expired| *expired).count()
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
+#[non_exhaustive]
+pub enum PaymentMethod {
+    Debit,
+    Credit
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acquirer::{DeclineKind, DeclineReason};
+    use crate::clock::FixedClock;
+    use crate::test_support::{approval, brl, card, merchant};
+    use chrono::TimeZone;
+
+    #[test]
+    fn should_create_a_txn() {
+        let card = card();
+        let transaction = Transaction::try_new(&merchant(), brl(2050), "A nice description".to_owned(), PaymentMethod::Debit, card.clone(), approval()).unwrap();
+        assert_eq!(transaction.value, brl(2050));
+        assert_eq!(transaction.description, "A nice description".to_owned());
+        assert_eq!(transaction.method, PaymentMethod::Debit);
+        assert_eq!(transaction.value, brl(2050));
+        assert_eq!(transaction.card, card);
+    }
+
+    #[test]
+    fn should_identify_each_transaction_and_its_merchant() {
+        let first = Transaction::try_new(&merchant(), brl(2050), "Test Transaction".to_owned(), PaymentMethod::Debit, card(), approval())
+            .unwrap()
+            .with_external_reference("order-42");
+        let second = Transaction::try_new(&merchant(), brl(2050), "Test Transaction".to_owned(), PaymentMethod::Debit, card(), approval()).unwrap();
+
+        assert_ne!(first.id(), second.id());
+        assert_eq!(first.id().to_string().parse(), Ok(first.id()));
+        assert_eq!(first.merchant_id(), "merchant-1");
+        assert_eq!(first.external_reference(), Some("order-42"));
+        assert_eq!(second.external_reference(), None);
+    }
+
+    #[test]
+    fn should_refuse_to_create_a_transaction_with_an_expired_card() {
+        let expired = Card::try_new("4111111111111111".to_owned(), "Rafael Dias".to_owned(), "01/24".to_owned()).unwrap();
+        let today = FixedClock::new(Utc.with_ymd_and_hms(2024, 2, 1, 12, 0, 0).unwrap());
+
+        let result = Transaction::try_new_at(&merchant(), brl(2050), "Test Transaction".to_owned(), PaymentMethod::Debit, expired.clone(), approval(), &today);
+
+        assert_eq!(result.unwrap_err(), TransactionError::ExpiredCard { expiry: Expiry::new(1, 2024).unwrap() });
+        let last_valid_day = FixedClock::new(Utc.with_ymd_and_hms(2024, 1, 31, 12, 0, 0).unwrap());
+        assert!(Transaction::try_new_at(&merchant(), brl(2050), "Test Transaction".to_owned(), PaymentMethod::Debit, expired, approval(), &last_valid_day).is_ok());
+    }
+
+    fn authorization(clock: &FixedClock) -> Transaction {
+        Transaction::authorize_at(&merchant(), brl(50000), "Hotel stay".to_owned(), PaymentMethod::Credit, card(), clock).unwrap()
+    }
+
+    #[test]
+    fn should_capture_part_of_an_authorization_within_the_window() {
+        let clock = FixedClock::new(Utc.with_ymd_and_hms(2024, 3, 15, 12, 0, 0).unwrap());
+        let mut tx = authorization(&clock);
+        assert_eq!((tx.status(), tx.captured()), (TransactionStatus::Authorized, brl(0)));
+        assert_eq!(tx.capture(brl(42000), &clock).unwrap_err(), TransactionError::NotApproved);
+
+        let mut tx = tx.with_authorization(approval()).unwrap();
+
+        assert_eq!(
+            tx.capture(brl(50001), &clock).unwrap_err(),
+            TransactionError::InvalidCaptureAmount { amount: brl(50001), authorized: brl(50000) }
+        );
+        tx.capture(brl(42000), &clock).unwrap();
+
+        assert_eq!((tx.status(), tx.captured()), (TransactionStatus::Captured, brl(42000)));
+        assert_eq!(
+            tx.void().unwrap_err(),
+            TransactionError::IllegalTransition { from: TransactionStatus::Captured, to: TransactionStatus::Voided }
+        );
+    }
+
+    #[test]
+    fn should_expire_authorizations_left_uncaptured() {
+        let clock = FixedClock::new(Utc.with_ymd_and_hms(2024, 3, 15, 12, 0, 0).unwrap());
+        let mut late = authorization(&clock).with_authorization(approval()).unwrap();
+        let mut voided = authorization(&clock);
+        let mut short = authorization(&clock).with_capture_window(Duration::days(1));
+        voided.void().unwrap();
+
+        let a_day_later = FixedClock::new(Utc.with_ymd_and_hms(2024, 3, 16, 12, 0, 0).unwrap());
+        assert_eq!(expire_authorizations([&mut late, &mut voided, &mut short], &a_day_later), 1);
+        assert_eq!(short.status(), TransactionStatus::Expired);
+
+        let a_week_later = FixedClock::new(Utc.with_ymd_and_hms(2024, 3, 22, 12, 0, 0).unwrap());
+        assert_eq!(
+            late.capture(brl(50000), &a_week_later).unwrap_err(),
+            TransactionError::AuthorizationExpired { deadline: Utc.with_ymd_and_hms(2024, 3, 22, 12, 0, 0).unwrap() }
+        );
+        assert_eq!((late.status(), voided.status()), (TransactionStatus::Expired, TransactionStatus::Voided));
+    }
+
+    #[test]
+    fn should_record_the_acquirer_answer_and_decline_what_it_turned_down() {
+        let clock = FixedClock::new(Utc.with_ymd_and_hms(2024, 3, 15, 12, 0, 0).unwrap());
+        let approved = authorization(&clock).with_authorization(AuthorizationResult::approved("000001", "000000001", "00").unwrap()).unwrap();
+        let declined = authorization(&clock)
+            .with_authorization(AuthorizationResult::declined(DeclineReason::InsufficientFunds, "000000002", "51").unwrap())
+            .unwrap();
+
+        assert!(approved.is_approved());
+        assert_eq!(approved.authorization().and_then(AuthorizationResult::approval_code), Some("000001"));
+        assert!(!declined.is_approved());
+        assert_eq!((declined.status(), declined.capture_deadline()), (TransactionStatus::Declined, None));
+        assert_eq!(declined.authorization().and_then(AuthorizationResult::decline_kind), Some(DeclineKind::Soft));
+        assert_eq!(
+            approved.with_authorization(AuthorizationResult::approved("000003", "000000003", "00").unwrap()).unwrap_err(),
+            TransactionError::AlreadyAuthorized
+        );
+    }
+
+    #[test]
+    fn should_only_split_credit_transactions_into_installments() {
+        let debit = Transaction::try_new(&merchant(), brl(10000), "Test Transaction".to_owned(), PaymentMethod::Debit, card(), approval()).unwrap();
+        assert_eq!(
+            debit.with_installments(2).unwrap_err(),
+            TransactionError::InvalidInstallments { method: PaymentMethod::Debit, installments: 2 }
+        );
+
+        let credit = Transaction::try_new(&merchant(), brl(10000), "Test Transaction".to_owned(), PaymentMethod::Credit, card(), approval()).unwrap();
+        assert_eq!(
+            credit.clone().with_installments(0).unwrap_err(),
+            TransactionError::InvalidInstallments { method: PaymentMethod::Credit, installments: 0 }
+        );
+        assert_eq!(
+            credit.with_installments(13).unwrap_err(),
+            TransactionError::InvalidInstallments { method: PaymentMethod::Credit, installments: 13 }
+        );
+    }
+}
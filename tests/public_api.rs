@@ -0,0 +1,26 @@
+use chrono::{TimeZone, Utc};
+use psp::acquirer::SimulatedAcquirer;
+use psp::card::{Card, SensitiveAuthData};
+use psp::clock::FixedClock;
+use psp::merchant::Merchant;
+use psp::money::{Currency, Money};
+use psp::payable::{Payable, PayableStatus};
+use psp::processor::Processor;
+use psp::transaction::{PaymentMethod, Transaction};
+
+#[test]
+fn should_turn_a_transaction_into_payables_from_outside_the_crate() {
+    let merchant = Merchant::new("merchant-1", chrono_tz::America::Sao_Paulo);
+    let card = Card::try_new("4111111111111111".to_owned(), "Rafael Dias".to_owned(), "12/30".to_owned()).unwrap();
+    let auth = SensitiveAuthData::try_new("123".to_owned(), &card).unwrap();
+    let clock = FixedClock::new(Utc.with_ymd_and_hms(2024, 3, 15, 12, 0, 0).unwrap());
+    let mut tx = Transaction::authorize_at(&merchant, Money::from_minor(10000, Currency::BRL), "Test Transaction".to_owned(), PaymentMethod::Debit, card, &clock)
+        .unwrap();
+
+    Processor::new(SimulatedAcquirer::new()).sale(&mut tx, auth, &clock).unwrap();
+    let payables = Payable::from_transaction(tx, &merchant).unwrap();
+
+    assert_eq!(payables.len(), 1);
+    assert_eq!(*payables[0].status(), PayableStatus::Paid);
+    assert_eq!(payables[0].net(), Money::from_minor(9700, Currency::BRL));
+}
@@ -0,0 +1,138 @@
+#![cfg(feature = "serde")]
+
+use chrono::{NaiveDate, TimeZone, Utc};
+use psp::acquirer::{AuthorizationError, AuthorizationResult};
+use psp::card::{Card, CardError};
+use psp::clock::FixedClock;
+use psp::dispute::{Dispute, DisputeError, DisputeReason, DisputeStatus};
+use psp::merchant::Merchant;
+use psp::money::{Currency, Money};
+use psp::payable::{Payable, PayableError, PayableStatus};
+use psp::transaction::{PaymentMethod, Transaction, TransactionError, TransactionStatus};
+use serde_json::json;
+
+fn card() -> Card {
+    Card::try_new("4111111111111111".to_owned(), "Rafael Dias".to_owned(), "12/30".to_owned()).unwrap()
+}
+
+fn brl(amount: i64) -> Money {
+    Money::from_minor(amount, Currency::BRL)
+}
+
+fn merchant() -> Merchant {
+    Merchant::new("merchant-1", chrono_tz::America::Sao_Paulo)
+}
+
+fn transaction() -> Transaction {
+    let clock = FixedClock::new(Utc.with_ymd_and_hms(2024, 3, 15, 12, 0, 0).unwrap());
+    let approval = AuthorizationResult::approved("000001", "000000001", "00").unwrap();
+    Transaction::try_new_at(&merchant(), Money::from_minor(10000, Currency::BRL), "Test Transaction".to_owned(), PaymentMethod::Credit, card(), approval, &clock).unwrap()
+}
+
+#[test]
+fn should_serialize_money_as_minor_units_and_enums_as_snake_case() {
+    let value = serde_json::to_value(transaction()).unwrap();
+
+    assert_eq!(value["value"], json!({ "amount": 10000, "currency": "BRL" }));
+    assert_eq!(value["method"], json!("credit"));
+    assert_eq!(value["created_at"], json!("2024-03-15T12:00:00Z"));
+    assert_eq!(
+        value["authorization"],
+        json!({ "outcome": { "approved": { "approval_code": "000001" } }, "nsu": "000000001", "response_code": "00" })
+    );
+    assert_eq!(serde_json::to_value(PayableStatus::WaitingFunds).unwrap(), json!("waiting_funds"));
+}
+
+#[test]
+fn should_serialize_only_the_truncated_card_number_and_the_holder_initials() {
+    let value = serde_json::to_value(card()).unwrap();
+
+    assert_eq!(value, json!({ "last_four": "1111", "bin": "41111111", "brand": "visa", "holder": "R. D." }));
+    assert!(!value.to_string().contains("4111111111111111"));
+    assert!(!value.to_string().contains("Rafael Dias"));
+    assert_eq!(serde_json::from_value::<Card>(value).unwrap().expires_at(), None);
+}
+
+#[test]
+fn should_refuse_to_deserialize_a_card_carrying_the_full_number() {
+    let payload = json!({ "last_four": "4111111111111111", "bin": "41111111", "brand": "visa", "holder": "R. D." });
+
+    let err = serde_json::from_value::<Card>(payload).unwrap_err();
+
+    assert_eq!(err.to_string(), CardError::InvalidTruncation { bin: 8, last_four: 16 }.to_string());
+}
+
+#[test]
+fn should_round_trip_payables() {
+    let payables = Payable::from_transaction(transaction().with_installments(2).unwrap(), &merchant()).unwrap();
+
+    let json = serde_json::to_string(&payables).unwrap();
+    let back: Vec<Payable> = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(serde_json::to_string(&back).unwrap(), json);
+    assert_eq!(back[1].installment().number(), 2);
+    assert_eq!(back[1].net(), payables[1].net());
+    assert_eq!(back[1].transaction().card().number(), "1111");
+}
+
+#[test]
+fn should_refuse_to_deserialize_a_payable_whose_amounts_do_not_add_up() {
+    let payable = Payable::from_transaction(transaction(), &merchant()).unwrap().remove(0);
+    let mut value = serde_json::to_value(&payable).unwrap();
+    value["net"] = json!({ "amount": 10000, "currency": "BRL" });
+
+    let err = serde_json::from_value::<Payable>(value).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        PayableError::InconsistentAmounts { gross: brl(10000), fee: brl(500), net: brl(10000) }.to_string()
+    );
+
+    let mut value = serde_json::to_value(&payable).unwrap();
+    value["status"] = json!("refunded");
+    assert!(serde_json::from_value::<Payable>(value).is_err());
+}
+
+#[test]
+fn should_refuse_to_deserialize_a_transaction_captured_beyond_its_value_or_without_approval() {
+    let mut value = serde_json::to_value(transaction()).unwrap();
+    value["captured"] = json!({ "amount": 10001, "currency": "BRL" });
+    let err = serde_json::from_value::<Transaction>(value).unwrap_err();
+    assert_eq!(err.to_string(), TransactionError::InvalidCaptureAmount { amount: brl(10001), authorized: brl(10000) }.to_string());
+
+    let mut value = serde_json::to_value(transaction()).unwrap();
+    value["captured"] = json!({ "amount": 10000, "currency": "USD" });
+    assert!(serde_json::from_value::<Transaction>(value).is_err());
+
+    let mut value = serde_json::to_value(transaction()).unwrap();
+    value["authorization"] = json!(null);
+    let err = serde_json::from_value::<Transaction>(value).unwrap_err();
+    assert_eq!(err.to_string(), TransactionError::InconsistentStatus { status: TransactionStatus::Captured }.to_string());
+}
+
+#[test]
+fn should_refuse_to_deserialize_an_approval_with_a_decline_code() {
+    let payload = json!({ "outcome": { "approved": { "approval_code": "000001" } }, "nsu": "000000001", "response_code": "51" });
+
+    let err = serde_json::from_value::<AuthorizationResult>(payload).unwrap_err();
+
+    assert_eq!(err.to_string(), AuthorizationError::ResponseCodeMismatch { response_code: "51".to_owned(), approved: true }.to_string());
+}
+
+#[test]
+fn should_refuse_to_deserialize_a_dispute_that_contradicts_itself() {
+    let mut payables = Payable::from_transaction(transaction(), &merchant()).unwrap();
+    let deadline = NaiveDate::from_ymd_opt(2024, 4, 30).unwrap();
+    let clock = FixedClock::new(Utc.with_ymd_and_hms(2024, 4, 10, 12, 0, 0).unwrap());
+    let dispute = Dispute::open(&mut payables, brl(10000), DisputeReason::Fraud, "10.4", deadline, &clock).unwrap();
+    let value = serde_json::to_value(&dispute).unwrap();
+    assert_eq!(serde_json::from_value::<Dispute>(value.clone()).unwrap(), dispute);
+
+    let mut won = value.clone();
+    won["status"] = json!("won");
+    let err = serde_json::from_value::<Dispute>(won).unwrap_err();
+    assert_eq!(err.to_string(), DisputeError::InconsistentStatus { status: DisputeStatus::Won }.to_string());
+
+    let mut mixed = value;
+    mixed["refunded_at_open"] = json!({ "amount": 0, "currency": "USD" });
+    assert!(serde_json::from_value::<Dispute>(mixed).is_err());
+}